@@ -4,6 +4,10 @@
 //! Rust strings into oniguruma char buffers to search and compile
 //! with.
 
+use std::mem;
+
+use super::Encoding;
+
 /// Encoded String Buffer
 ///
 /// Represents a buffer of characters with encoding information
@@ -22,8 +26,8 @@ pub trait EncodedChars {
     fn limit_ptr(&self) -> *const onig_sys::OnigUChar;
 
     /// The encoding of the contents of the buffer
-    fn encoding(&self) -> onig_sys::OnigEncoding {
-        unsafe { &mut onig_sys::OnigEncodingUTF8 }
+    fn encoding(&self) -> Encoding {
+        Encoding::Utf8
     }
 
     /// The length of this buffer
@@ -59,7 +63,7 @@ where
 /// Represents a buffer of bytes, with an encoding.
 pub struct EncodedBytes<'a> {
     bytes: &'a [u8],
-    enc: onig_sys::OnigEncoding,
+    enc: Encoding,
 }
 
 impl<'a> EncodedBytes<'a> {
@@ -73,7 +77,7 @@ impl<'a> EncodedBytes<'a> {
     /// # Returns
     ///
     /// A new buffer instance
-    pub fn from_parts(bytes: &'a [u8], enc: onig_sys::OnigEncoding) -> EncodedBytes<'a> {
+    pub fn from_parts(bytes: &'a [u8], enc: Encoding) -> EncodedBytes<'a> {
         EncodedBytes { bytes, enc }
     }
 
@@ -89,7 +93,7 @@ impl<'a> EncodedBytes<'a> {
     pub fn ascii(bytes: &'a [u8]) -> EncodedBytes<'a> {
         EncodedBytes {
             bytes,
-            enc: unsafe { &mut onig_sys::OnigEncodingASCII },
+            enc: Encoding::Ascii,
         }
     }
 }
@@ -103,7 +107,7 @@ impl<'a> EncodedChars for EncodedBytes<'a> {
         self.bytes[self.bytes.len()..].as_ptr()
     }
 
-    fn encoding(&self) -> onig_sys::OnigEncoding {
+    fn encoding(&self) -> Encoding {
         self.enc
     }
 
@@ -112,33 +116,202 @@ impl<'a> EncodedChars for EncodedBytes<'a> {
     }
 }
 
+/// Code Unit
+///
+/// A fixed width code unit which can be searched in place with the
+/// native-endian variant of its encoding. Implemented for `u16`
+/// (UTF-16), `u32` and `char` (UTF-32).
+pub trait CodeUnit: Copy + private::Sealed {
+    /// The encoding of a slice of these units
+    const ENCODING: Encoding;
+}
+
+impl CodeUnit for u16 {
+    #[cfg(target_endian = "little")]
+    const ENCODING: Encoding = Encoding::Utf16Le;
+    #[cfg(target_endian = "big")]
+    const ENCODING: Encoding = Encoding::Utf16Be;
+}
+
+impl CodeUnit for u32 {
+    #[cfg(target_endian = "little")]
+    const ENCODING: Encoding = Encoding::Utf32Le;
+    #[cfg(target_endian = "big")]
+    const ENCODING: Encoding = Encoding::Utf32Be;
+}
+
+impl CodeUnit for char {
+    const ENCODING: Encoding = u32::ENCODING;
+}
+
+mod private {
+    pub trait Sealed {}
+    impl Sealed for u16 {}
+    impl Sealed for u32 {}
+    impl Sealed for char {}
+}
+
+/// Code Unit Buffer
+///
+/// Represents a slice of UTF-16 or UTF-32 code units, such as a
+/// Windows or JavaScript string held as `&[u16]`, or a `&[char]`
+/// buffer. The units are searched in place using the native-endian
+/// encoding for the unit type.
+///
+/// Positions reported by a `Regex` are always byte offsets into the
+/// buffer. Use `unit_offset` and `unit_pos` to convert them to code
+/// unit offsets.
+///
+/// ```rust
+/// use onig::{EncodedUnits, Regex};
+///
+/// let pattern: Vec<u16> = "l+".encode_utf16().collect();
+/// let text: Vec<u16> = "héllo".encode_utf16().collect();
+/// let regex = Regex::with_encoding(EncodedUnits::new(&pattern)).unwrap();
+///
+/// let text = EncodedUnits::new(&text);
+/// let pos = regex.find_with_encoding(text).unwrap();
+/// assert_eq!(pos, (4, 8));
+/// assert_eq!(text.unit_pos(pos), (2, 4));
+/// ```
+#[derive(Debug, Clone, Copy)]
+pub struct EncodedUnits<'a, U> {
+    units: &'a [U],
+}
+
+impl<'a, U: CodeUnit> EncodedUnits<'a, U> {
+    /// New Buffer from Code Units
+    ///
+    /// # Arguments
+    ///
+    ///  * `units` - The code units to search, in native byte order
+    ///
+    /// # Returns
+    ///
+    /// A new buffer instance
+    pub fn new(units: &'a [U]) -> EncodedUnits<'a, U> {
+        EncodedUnits { units }
+    }
+
+    /// The code units in this buffer
+    pub fn units(&self) -> &'a [U] {
+        self.units
+    }
+
+    /// Convert a Byte Offset to a Code Unit Offset
+    ///
+    /// # Arguments
+    ///
+    ///  * `offset` - A byte offset into this buffer, such as the
+    ///    start or end of a match
+    pub fn unit_offset(&self, offset: usize) -> usize {
+        offset / mem::size_of::<U>()
+    }
+
+    /// Convert a Code Unit Offset to a Byte Offset
+    ///
+    /// # Arguments
+    ///
+    ///  * `offset` - An offset into this buffer in code units, such as
+    ///    a position to start searching from
+    pub fn byte_offset(&self, offset: usize) -> usize {
+        offset * mem::size_of::<U>()
+    }
+
+    /// Convert a Byte Position to a Code Unit Position
+    ///
+    /// Converts a `(start, end)` pair of byte offsets, as returned by
+    /// `Regex::find_with_encoding` or `Region::pos`, to code units.
+    pub fn unit_pos(&self, pos: (usize, usize)) -> (usize, usize) {
+        (self.unit_offset(pos.0), self.unit_offset(pos.1))
+    }
+}
+
+impl<'a, U: CodeUnit> From<&'a [U]> for EncodedUnits<'a, U> {
+    fn from(units: &'a [U]) -> Self {
+        EncodedUnits::new(units)
+    }
+}
+
+impl<'a, U: CodeUnit> EncodedChars for EncodedUnits<'a, U> {
+    fn start_ptr(&self) -> *const onig_sys::OnigUChar {
+        self.units.as_ptr() as *const onig_sys::OnigUChar
+    }
+
+    fn limit_ptr(&self) -> *const onig_sys::OnigUChar {
+        self.units.as_ptr_range().end as *const onig_sys::OnigUChar
+    }
+
+    fn encoding(&self) -> Encoding {
+        U::ENCODING
+    }
+
+    fn len(&self) -> usize {
+        mem::size_of_val(self.units)
+    }
+}
+
+/// Character Length
+///
+/// Returns the length in bytes of the character at the start of
+/// `bytes`, assuming it is encoded with `enc`. Always returns at least
+/// one, so it can be used to step past empty matches.
+pub(crate) fn char_len(enc: Encoding, bytes: &[u8]) -> usize {
+    if bytes.is_empty() {
+        return 1;
+    }
+    mbc_enc_len(enc, bytes).min(bytes.len())
+}
+
+/// Encoded Character Length
+///
+/// Returns the length in bytes the character at the start of `bytes`
+/// claims to have, which may be more than are left. Oniguruma's
+/// `mbc_enc_len` doesn't take the end of the buffer, and reads past
+/// the first byte for encodings such as UTF-16, so a short tail is
+/// copied into a zero padded buffer first.
+pub(crate) fn mbc_enc_len(enc: Encoding, bytes: &[u8]) -> usize {
+    let mbc_enc_len = match unsafe { (*enc.as_raw()).mbc_enc_len } {
+        Some(f) if !bytes.is_empty() => f,
+        _ => return 1,
+    };
+    let len = if bytes.len() >= enc.max_char_len() {
+        unsafe { mbc_enc_len(bytes.as_ptr()) }
+    } else {
+        let mut padded = [0; onig_sys::ONIGENC_CODE_TO_MBC_MAXLEN as usize];
+        padded[..bytes.len()].copy_from_slice(bytes);
+        unsafe { mbc_enc_len(padded.as_ptr()) }
+    };
+    len.max(1) as usize
+}
+
 #[cfg(test)]
 pub mod tests {
     use super::*;
 
+    #[test]
+    pub fn char_len_stops_at_end_of_buffer() {
+        assert_eq!(char_len(Encoding::Utf16Le, &[b'a', 0, b'b']), 2);
+        assert_eq!(char_len(Encoding::Utf16Le, b"b"), 1);
+        assert_eq!(mbc_enc_len(Encoding::Utf16Le, &[0, 0xd8]), 4);
+        assert_eq!(char_len(Encoding::Utf16Le, &[0, 0xd8, 0]), 3);
+        assert_eq!(char_len(Encoding::Gb18030, &[0x81]), 1);
+    }
+
     #[test]
     pub fn rust_string_encoding_is_utf8() {
         let foo = "foo";
-        assert_eq!(
-            unsafe { &mut onig_sys::OnigEncodingUTF8 } as onig_sys::OnigEncoding,
-            foo.encoding()
-        );
+        assert_eq!(Encoding::Utf8, foo.encoding());
 
         let bar = String::from(".*");
-        assert_eq!(
-            unsafe { &mut onig_sys::OnigEncodingUTF8 } as onig_sys::OnigEncoding,
-            bar.encoding()
-        );
+        assert_eq!(Encoding::Utf8, bar.encoding());
     }
 
     #[test]
     pub fn rust_bytes_encoding_is_ascii() {
         let fizz = b"fizz";
         let buff = EncodedBytes::ascii(fizz);
-        assert_eq!(
-            unsafe { &mut onig_sys::OnigEncodingASCII } as onig_sys::OnigEncoding,
-            buff.encoding()
-        );
+        assert_eq!(Encoding::Ascii, buff.encoding());
     }
 
     #[test]
@@ -163,15 +336,30 @@ pub mod tests {
     #[test]
     pub fn byte_buffer_create() {
         let buff = b"hello world";
-        let enc_buffer =
-            EncodedBytes::from_parts(buff, unsafe { &mut onig_sys::OnigEncodingASCII });
+        let enc_buffer = EncodedBytes::from_parts(buff, Encoding::Ascii);
+        assert_eq!(Encoding::Ascii, enc_buffer.encoding());
         assert_eq!(
-            unsafe { &mut onig_sys::OnigEncodingASCII } as onig_sys::OnigEncoding,
-            enc_buffer.encoding()
+            enc_buffer.limit_ptr() as usize - enc_buffer.start_ptr() as usize,
+            buff.len()
         );
+    }
+
+    #[test]
+    pub fn code_unit_buffers() {
+        let utf16: Vec<u16> = "héllo".encode_utf16().collect();
+        let buff = EncodedUnits::new(&utf16);
+        assert_eq!(buff.len(), 10);
         assert_eq!(
-            enc_buffer.limit_ptr() as usize - enc_buffer.start_ptr() as usize,
+            buff.limit_ptr() as usize - buff.start_ptr() as usize,
             buff.len()
         );
+        assert_eq!(buff.unit_pos((4, 8)), (2, 4));
+        assert_eq!(buff.byte_offset(3), 6);
+
+        let chars: Vec<char> = "héllo".chars().collect();
+        let buff = EncodedUnits::from(&chars[..]);
+        assert_eq!(buff.encoding(), EncodedUnits::<u32>::new(&[]).encoding());
+        assert_eq!(buff.len(), 20);
+        assert_eq!(buff.unit_offset(8), 2);
     }
 }
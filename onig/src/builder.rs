@@ -0,0 +1,311 @@
+//! Regex Builder
+//!
+//! Contains the definition of `RegexBuilder`. This collects all of
+//! the compile time settings for a regex in one place before
+//! compiling it.
+
+use std::slice;
+
+use super::{
+    CaseFoldFlags, EncodedBytes, EncodedChars, Encoding, Error, Regex, RegexOptions, Syntax,
+};
+
+/// Regex Builder
+///
+/// Configures and compiles a new `Regex`. Each setting has a sensible
+/// default, so only the ones which differ from the defaults used by
+/// `Regex::new` need to be given.
+///
+/// # Examples
+///
+/// ```
+/// use onig::{RegexBuilder, RegexOptions, Syntax};
+///
+/// let regex = RegexBuilder::new(r"hello\s+(\w+)")
+///     .options(RegexOptions::REGEX_OPTION_IGNORECASE)
+///     .syntax(Syntax::perl())
+///     .retry_limit_in_match(10_000)
+///     .build()
+///     .unwrap();
+/// assert_eq!(regex.find("Say HELLO World"), Some((4, 15)));
+/// ```
+#[derive(Debug, Clone)]
+pub struct RegexBuilder<'s> {
+    pattern: Vec<u8>,
+    encoding: Encoding,
+    target_encoding: Option<Encoding>,
+    options: RegexOptions,
+    syntax: &'s Syntax,
+    case_fold: Option<CaseFoldFlags>,
+    retry_limit_in_match: Option<u32>,
+    match_stack_limit: Option<u32>,
+}
+
+impl<'s> RegexBuilder<'s> {
+    /// Create a Regex Builder
+    ///
+    /// Starts building a regex for `pattern`. The encoding of the
+    /// regex is taken from the `pattern` buffer, so a `&str` pattern
+    /// builds a UTF-8 regex.
+    ///
+    /// # Arguments
+    ///
+    ///  * `pattern` - The regex pattern to compile.
+    pub fn new<T>(pattern: T) -> RegexBuilder<'s>
+    where
+        T: EncodedChars,
+    {
+        let bytes = unsafe { slice::from_raw_parts(pattern.start_ptr(), pattern.len()) };
+        RegexBuilder {
+            pattern: bytes.to_vec(),
+            encoding: pattern.encoding(),
+            target_encoding: None,
+            options: RegexOptions::REGEX_OPTION_NONE,
+            syntax: Syntax::default(),
+            case_fold: None,
+            retry_limit_in_match: None,
+            match_stack_limit: None,
+        }
+    }
+
+    /// Set the regex compilation options.
+    pub fn options(&mut self, options: RegexOptions) -> &mut Self {
+        self.options = options;
+        self
+    }
+
+    /// Set the syntax which the pattern is written in.
+    pub fn syntax(&mut self, syntax: &'s Syntax) -> &mut Self {
+        self.syntax = syntax;
+        self
+    }
+
+    /// Set the encoding of the pattern
+    ///
+    /// Overrides the encoding taken from the pattern buffer. Strings
+    /// searched with the compiled regex must use the same encoding.
+    pub fn encoding(&mut self, encoding: Encoding) -> &mut Self {
+        self.encoding = encoding;
+        self
+    }
+
+    /// Set the encoding of the strings to search
+    ///
+    /// By default strings searched with the compiled regex must be in
+    /// the same encoding as the pattern. This allows a pattern, such
+    /// as a UTF-8 `&str`, to be compiled for searching strings in a
+    /// different encoding.
+    ///
+    /// Oniguruma can't convert patterns between encodings. A pattern
+    /// made up only of ASCII characters can be compiled for any ASCII
+    /// compatible target. Any other combination fails to build with an
+    /// `ErrorKind::EncodingMismatch` error.
+    ///
+    /// ```rust
+    /// use onig::{EncodedBytes, Encoding, ErrorKind, RegexBuilder};
+    ///
+    /// let regex = RegexBuilder::new(r"\w+")
+    ///     .target_encoding(Encoding::Sjis)
+    ///     .build()
+    ///     .unwrap();
+    /// assert_eq!(regex.encoding(), Encoding::Sjis);
+    /// let text = EncodedBytes::from_parts(b"\x93\xfa\x96\x7b", Encoding::Sjis);
+    /// assert_eq!(regex.find_with_encoding(text), Some((0, 4)));
+    ///
+    /// let e = RegexBuilder::new("日本")
+    ///     .target_encoding(Encoding::Sjis)
+    ///     .build()
+    ///     .unwrap_err();
+    /// assert_eq!(e.kind(), ErrorKind::EncodingMismatch);
+    /// ```
+    pub fn target_encoding(&mut self, encoding: Encoding) -> &mut Self {
+        self.target_encoding = Some(encoding);
+        self
+    }
+
+    /// Set the case folding flags
+    ///
+    /// Controls how `REGEX_OPTION_IGNORECASE` compares characters. If
+    /// this isn't set then Oniguruma's global default is used.
+    pub fn case_fold(&mut self, case_fold: CaseFoldFlags) -> &mut Self {
+        self.case_fold = Some(case_fold);
+        self
+    }
+
+    /// Set the retry limit in match
+    ///
+    /// Used by every match and search with the compiled regex which
+    /// doesn't take an explicit `MatchParam`.
+    pub fn retry_limit_in_match(&mut self, limit: u32) -> &mut Self {
+        self.retry_limit_in_match = Some(limit);
+        self
+    }
+
+    /// Set the match stack limit
+    ///
+    /// Used by every match and search with the compiled regex which
+    /// doesn't take an explicit `MatchParam`.
+    pub fn match_stack_limit(&mut self, limit: u32) -> &mut Self {
+        self.match_stack_limit = Some(limit);
+        self
+    }
+
+    /// Compile the Regex
+    ///
+    /// Compiles the pattern with the current settings. If the pattern
+    /// is invalid the returned `Error` contains more information.
+    pub fn build(&self) -> Result<Regex, Error> {
+        let pattern = EncodedBytes::from_parts(&self.pattern, self.encoding);
+        let case_fold = self.case_fold.unwrap_or_else(Regex::default_case_fold);
+        let target = self.target_encoding.unwrap_or(self.encoding);
+        let mut regex = Regex::compile(pattern, target, self.options, self.syntax, case_fold)?;
+        regex.retry_limit_in_match = self.retry_limit_in_match;
+        regex.match_stack_limit = self.match_stack_limit;
+        Ok(regex)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::*;
+
+    #[test]
+    fn test_builder_defaults() {
+        let regex = RegexBuilder::new("e(l+)").build().unwrap();
+        assert_eq!(regex.captures_len(), 1);
+        assert_eq!(regex.find("hello"), Some((1, 4)));
+    }
+
+    #[test]
+    fn test_builder_options_and_syntax() {
+        let regex = RegexBuilder::new("HELLO")
+            .options(RegexOptions::REGEX_OPTION_IGNORECASE)
+            .build()
+            .unwrap();
+        assert!(regex.is_match("hello"));
+
+        let asis = RegexBuilder::new("a.c")
+            .syntax(Syntax::asis())
+            .build()
+            .unwrap();
+        assert_eq!(asis.find("abc a.c"), Some((4, 7)));
+    }
+
+    #[test]
+    fn test_builder_encoding() {
+        let regex = RegexBuilder::new(EncodedBytes::ascii(b"l+"))
+            .build()
+            .unwrap();
+        assert_eq!(regex.encoding(), EncodedBytes::ascii(b"").encoding());
+
+        let regex = RegexBuilder::new("l+")
+            .encoding(EncodedBytes::ascii(b"").encoding())
+            .build()
+            .unwrap();
+        assert_eq!(
+            regex.find_with_encoding(EncodedBytes::ascii(b"hello")),
+            Some((2, 4))
+        );
+    }
+
+    #[test]
+    fn test_builder_target_encoding() {
+        let regex = RegexBuilder::new("caf.")
+            .target_encoding(Encoding::Iso8859_1)
+            .build()
+            .unwrap();
+        assert_eq!(regex.encoding(), Encoding::Iso8859_1);
+        let text = EncodedBytes::from_parts(b"un caf\xe9", Encoding::Iso8859_1);
+        assert_eq!(regex.find_with_encoding(text), Some((3, 7)));
+
+        let e = RegexBuilder::new("café")
+            .target_encoding(Encoding::Iso8859_1)
+            .build()
+            .unwrap_err();
+        assert_eq!(e.kind(), ErrorKind::EncodingMismatch);
+        assert_eq!(
+            e.code(),
+            onig_sys::ONIGERR_NOT_SUPPORTED_ENCODING_COMBINATION
+        );
+
+        let e = RegexBuilder::new("a")
+            .target_encoding(Encoding::Utf16Le)
+            .build()
+            .unwrap_err();
+        assert_eq!(e.kind(), ErrorKind::EncodingMismatch);
+    }
+
+    #[test]
+    fn test_builder_case_fold() {
+        let regex = RegexBuilder::new("ss")
+            .options(RegexOptions::REGEX_OPTION_IGNORECASE)
+            .build()
+            .unwrap();
+        assert!(regex.is_match("ß"));
+
+        let regex = RegexBuilder::new("ss")
+            .options(RegexOptions::REGEX_OPTION_IGNORECASE)
+            .case_fold(CaseFoldFlags::empty())
+            .build()
+            .unwrap();
+        assert!(!regex.is_match("ß"));
+        assert_eq!(regex.case_fold_flag(), CaseFoldFlags::empty());
+    }
+
+    #[test]
+    fn test_builder_case_fold_flag() {
+        let regex = RegexBuilder::new("é").build().unwrap();
+        assert_eq!(regex.case_fold_flag(), Regex::default_case_fold());
+
+        let turkish = CaseFoldFlags::CASE_FOLD_MIN | CaseFoldFlags::CASE_FOLD_TURKISH_AZERI;
+        let regex = RegexBuilder::new("é").case_fold(turkish).build().unwrap();
+        assert_eq!(regex.case_fold_flag(), turkish);
+
+        let regex = RegexBuilder::new("é")
+            .options(RegexOptions::REGEX_OPTION_IGNORECASE)
+            .case_fold(CaseFoldFlags::CASE_FOLD_ASCII_ONLY)
+            .build()
+            .unwrap();
+        assert!(regex.is_match("é"));
+        assert!(!regex.is_match("É"));
+    }
+
+    #[cfg(feature = "case-fold-turkish-azeri")]
+    #[test]
+    fn test_builder_case_fold_turkish_azeri() {
+        let build = |case_fold| {
+            RegexBuilder::new("I")
+                .options(RegexOptions::REGEX_OPTION_IGNORECASE)
+                .case_fold(case_fold)
+                .build()
+                .unwrap()
+        };
+
+        let regex = build(CaseFoldFlags::CASE_FOLD_MIN);
+        assert!(regex.is_match("i"));
+        assert!(!regex.is_match("ı"));
+
+        let regex = build(CaseFoldFlags::CASE_FOLD_MIN | CaseFoldFlags::CASE_FOLD_TURKISH_AZERI);
+        assert!(regex.is_match("ı"));
+        assert!(!regex.is_match("i"));
+    }
+
+    #[test]
+    fn test_builder_match_limits() {
+        let regex = RegexBuilder::new("(a|b|ab)*bc")
+            .retry_limit_in_match(10)
+            .build()
+            .unwrap();
+        let result = std::panic::catch_unwind(|| regex.find("abababababababababc"));
+        assert!(result.is_err());
+
+        let regex = RegexBuilder::new("(a|b|ab)*bc").build().unwrap();
+        assert_eq!(regex.find("abababababababababc"), Some((0, 19)));
+    }
+
+    #[test]
+    fn test_builder_invalid_pattern() {
+        let e = RegexBuilder::new("\\p{foo}").build().unwrap_err();
+        assert_eq!(e.code(), -223);
+    }
+}
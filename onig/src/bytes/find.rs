@@ -0,0 +1,622 @@
+use super::super::buffers::char_len;
+use super::super::names::NameTable;
+use super::super::{Region, SearchOptions};
+use super::Regex;
+use std::iter::FusedIterator;
+use std::ops::Index;
+use std::slice;
+
+impl Regex {
+    /// Returns the capture groups corresponding to the leftmost-first match
+    /// in text. Capture group `0` always corresponds to the entire match.
+    /// If no match is found, then `None` is returned.
+    pub fn captures<'t>(&self, text: &'t [u8]) -> Option<Captures<'t>> {
+        let mut region = Region::new();
+        self.search_with_options(
+            text,
+            0,
+            text.len(),
+            SearchOptions::SEARCH_OPTION_NONE,
+            Some(&mut region),
+        )
+        .map(|pos| Captures {
+            names: self.inner.name_table(),
+            text,
+            region,
+            offset: pos,
+        })
+    }
+
+    /// Returns an iterator for each successive non-overlapping match in `text`,
+    /// returning the start and end byte indices with respect to `text`.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// # use onig::bytes::Regex;
+    /// let re = Regex::new(r"\d+").unwrap();
+    /// let ms = re.find_iter(b"a12\xffb2").collect::<Vec<_>>();
+    /// assert_eq!(ms, vec![(1, 3), (5, 6)]);
+    /// ```
+    pub fn find_iter<'r, 't>(&'r self, text: &'t [u8]) -> FindMatches<'r, 't> {
+        FindMatches {
+            regex: self,
+            region: Region::new(),
+            text,
+            last_end: 0,
+            last_match_end: None,
+        }
+    }
+
+    /// Returns an iterator over all the non-overlapping capture groups matched
+    /// in `text`. This is operationally the same as `find_iter` (except it
+    /// yields information about submatches).
+    pub fn captures_iter<'r, 't>(&'r self, text: &'t [u8]) -> FindCaptures<'r, 't> {
+        FindCaptures {
+            regex: self,
+            text,
+            last_end: 0,
+            last_match_end: None,
+        }
+    }
+
+    /// Returns an iterator of slices of `text` delimited by a match
+    /// of the regular expression.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// # use onig::bytes::Regex;
+    /// let re = Regex::new(r"[ \t]+").unwrap();
+    /// let fields: Vec<&[u8]> = re.split(b"a b \t  c").collect();
+    /// assert_eq!(fields, vec![&b"a"[..], &b"b"[..], &b"c"[..]]);
+    /// ```
+    pub fn split<'r, 't>(&'r self, text: &'t [u8]) -> RegexSplits<'r, 't> {
+        RegexSplits {
+            finder: self.find_iter(text),
+            last: 0,
+        }
+    }
+
+    /// Returns an iterator of at most `limit` slices of `text`
+    /// delimited by a match of the regular expression. (A `limit` of
+    /// `0` will return no slices.) The remainder of the slice that is
+    /// not split will be the last element in the iterator.
+    pub fn splitn<'r, 't>(&'r self, text: &'t [u8], limit: usize) -> RegexSplitsN<'r, 't> {
+        RegexSplitsN {
+            splits: self.split(text),
+            n: limit,
+        }
+    }
+
+    /// Scan the given slice, capturing into the given region and
+    /// executing a callback for each match.
+    pub fn scan_with_region<F>(
+        &self,
+        to_search: &[u8],
+        region: &mut Region,
+        options: SearchOptions,
+        callback: F,
+    ) -> i32
+    where
+        F: Fn(i32, i32, &Region) -> bool,
+    {
+        self.inner
+            .scan_with_encoding(self.encoded(to_search), region, options, callback)
+    }
+
+    /// Scan a Pattern and Observe Captures
+    ///
+    /// The scan function takes a haystack `to_search` and invokes the
+    /// given `callback` for each capture of this expression.
+    pub fn scan<'t, CB>(&self, to_search: &'t [u8], callback: CB)
+    where
+        CB: Fn(i32, Captures<'t>) -> bool,
+    {
+        let mut region = Region::new();
+        let names = self.inner.name_table();
+        self.scan_with_region(
+            to_search,
+            &mut region,
+            SearchOptions::SEARCH_OPTION_NONE,
+            |n, s, region| {
+                let captures = Captures {
+                    names: names.clone(),
+                    text: to_search,
+                    region: region.clone(),
+                    offset: s as usize,
+                };
+                callback(n, captures)
+            },
+        );
+    }
+
+    /// Search from `last_end`, skipping empty matches which
+    /// immediately follow the previous match.
+    fn next_match(
+        &self,
+        text: &[u8],
+        region: &mut Region,
+        last_end: &mut usize,
+        last_match_end: &mut Option<usize>,
+    ) -> Option<usize> {
+        loop {
+            if *last_end > text.len() {
+                return None;
+            }
+            region.clear();
+            let r = self.search_with_options(
+                text,
+                *last_end,
+                text.len(),
+                SearchOptions::SEARCH_OPTION_NONE,
+                Some(region),
+            )?;
+            let (s, e) = region.pos(0).unwrap();
+
+            // Don't accept empty matches immediately following the last match.
+            // i.e., no infinite loops please.
+            if e == s && *last_match_end == Some(e) {
+                *last_end += char_len(self.encoding(), &text[*last_end..]);
+            } else {
+                *last_end = e;
+                *last_match_end = Some(e);
+                return Some(r);
+            }
+        }
+    }
+}
+
+/// Captures represents a group of captured byte slices for a single
+/// match.
+///
+/// The 0th capture always corresponds to the entire match. Each subsequent
+/// index corresponds to the next capture group in the regex. Positions
+/// returned from a capture group are always byte indices.
+///
+/// `'t` is the lifetime of the matched text.
+#[derive(Debug)]
+pub struct Captures<'t> {
+    names: NameTable,
+    text: &'t [u8],
+    region: Region,
+    offset: usize,
+}
+
+impl<'t> Captures<'t> {
+    /// Returns the start and end positions of the Nth capture group. Returns
+    /// `None` if i is not a valid capture group or if the capture group did
+    /// not match anything. The positions returned are always byte indices with
+    /// respect to the original slice matched.
+    pub fn pos(&self, pos: usize) -> Option<(usize, usize)> {
+        self.region.pos(pos)
+    }
+
+    /// Returns the matched bytes for the capture group `i`. If `i` isn't
+    /// a valid capture group or didn't match anything, then `None` is returned.
+    pub fn at(&self, pos: usize) -> Option<&'t [u8]> {
+        self.pos(pos).map(|(beg, end)| &self.text[beg..end])
+    }
+
+    /// Returns the start and end positions of the capture group called
+    /// `name`. Returns `None` if there is no group with that name or if
+    /// the group did not match anything.
+    ///
+    /// If more than one group has the same name, the last of them
+    /// which took part in the match is used.
+    pub fn name_pos(&self, name: &str) -> Option<(usize, usize)> {
+        self.names
+            .group(name, &self.region)
+            .and_then(|group| self.pos(group))
+    }
+
+    /// Returns the matched bytes for the capture group called `name`.
+    /// If there is no group with that name or it didn't match anything,
+    /// then `None` is returned.
+    pub fn name(&self, name: &str) -> Option<&'t [u8]> {
+        self.name_pos(name).map(|(beg, end)| &self.text[beg..end])
+    }
+
+    /// Returns the number of captured groups.
+    pub fn len(&self) -> usize {
+        self.region.len()
+    }
+
+    /// Returns true if and only if there are no captured groups.
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Creates an iterator of all the capture groups in order of appearance in
+    /// the regular expression.
+    pub fn iter(&'t self) -> SubCaptures<'t> {
+        SubCaptures { idx: 0, caps: self }
+    }
+
+    /// Creates an iterator of all the capture group positions in order of
+    /// appearance in the regular expression. Positions are byte indices in
+    /// terms of the original slice matched.
+    pub fn iter_pos(&'t self) -> SubCapturesPos<'t> {
+        SubCapturesPos { idx: 0, caps: self }
+    }
+
+    /// Creates an iterator of the named capture groups, yielding the
+    /// name of each group along with the bytes it matched. Each name
+    /// appears once, even if it is used by more than one group, and the
+    /// names are ordered by the first group which uses them.
+    pub fn iter_names(&'t self) -> SubCapturesNames<'t> {
+        SubCapturesNames {
+            names: self.names.entries().iter(),
+            caps: self,
+        }
+    }
+
+    /// Offset of the captures within the given slice.
+    pub fn offset(&self) -> usize {
+        self.offset
+    }
+}
+
+/// An iterator over capture groups for a particular match of a regular
+/// expression.
+///
+/// `'t` is the lifetime of the matched text.
+pub struct SubCaptures<'t> {
+    idx: usize,
+    caps: &'t Captures<'t>,
+}
+
+impl<'t> Iterator for SubCaptures<'t> {
+    type Item = Option<&'t [u8]>;
+
+    fn next(&mut self) -> Option<Option<&'t [u8]>> {
+        if self.idx < self.caps.len() {
+            self.idx += 1;
+            Some(self.caps.at(self.idx - 1))
+        } else {
+            None
+        }
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let size = self.caps.len();
+        (size, Some(size))
+    }
+
+    fn count(self) -> usize {
+        self.caps.len()
+    }
+}
+
+impl<'t> FusedIterator for SubCaptures<'t> {}
+
+impl<'t> ExactSizeIterator for SubCaptures<'t> {}
+
+/// An iterator over capture group positions for a particular match of
+/// a regular expression.
+///
+/// Positions are byte indices in terms of the original slice
+/// matched. `'t` is the lifetime of the matched text.
+pub struct SubCapturesPos<'t> {
+    idx: usize,
+    caps: &'t Captures<'t>,
+}
+
+impl<'t> Iterator for SubCapturesPos<'t> {
+    type Item = Option<(usize, usize)>;
+
+    fn next(&mut self) -> Option<Option<(usize, usize)>> {
+        if self.idx < self.caps.len() {
+            self.idx += 1;
+            Some(self.caps.pos(self.idx - 1))
+        } else {
+            None
+        }
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let size = self.caps.len();
+        (size, Some(size))
+    }
+
+    fn count(self) -> usize {
+        self.caps.len()
+    }
+}
+
+impl<'t> FusedIterator for SubCapturesPos<'t> {}
+
+impl<'t> ExactSizeIterator for SubCapturesPos<'t> {}
+
+/// Get a named group with `caps["name"]`
+///
+/// # Panics
+///
+/// If there is no group with the given name, or the group didn't
+/// match anything.
+impl<'t> Index<&str> for Captures<'t> {
+    type Output = [u8];
+
+    fn index(&self, name: &str) -> &[u8] {
+        self.name(name)
+            .unwrap_or_else(|| panic!("no group named '{}' matched", name))
+    }
+}
+
+/// An iterator over the named capture groups for a particular match
+/// of a regular expression.
+///
+/// Each item is the name of a group and the bytes it matched, if
+/// any. `'t` is the lifetime of the matched text.
+pub struct SubCapturesNames<'t> {
+    names: slice::Iter<'t, (Box<str>, Box<[usize]>)>,
+    caps: &'t Captures<'t>,
+}
+
+impl<'t> Iterator for SubCapturesNames<'t> {
+    type Item = (&'t str, Option<&'t [u8]>);
+
+    fn next(&mut self) -> Option<(&'t str, Option<&'t [u8]>)> {
+        self.names
+            .next()
+            .map(|(name, _)| (&**name, self.caps.name(name)))
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.names.size_hint()
+    }
+}
+
+impl<'t> FusedIterator for SubCapturesNames<'t> {}
+
+impl<'t> ExactSizeIterator for SubCapturesNames<'t> {}
+
+/// An iterator over all non-overlapping matches for a particular
+/// byte slice.
+///
+/// The iterator yields a tuple of integers corresponding to the start and end
+/// of the match. The indices are byte offsets. The iterator stops when no more
+/// matches can be found.
+///
+/// `'r` is the lifetime of the `Regex` struct and `'t` is the lifetime
+/// of the matched slice.
+pub struct FindMatches<'r, 't> {
+    regex: &'r Regex,
+    region: Region,
+    text: &'t [u8],
+    last_end: usize,
+    last_match_end: Option<usize>,
+}
+
+impl<'r, 't> Iterator for FindMatches<'r, 't> {
+    type Item = (usize, usize);
+
+    fn next(&mut self) -> Option<(usize, usize)> {
+        self.regex.next_match(
+            self.text,
+            &mut self.region,
+            &mut self.last_end,
+            &mut self.last_match_end,
+        )?;
+        self.region.pos(0)
+    }
+}
+
+impl<'r, 't> FusedIterator for FindMatches<'r, 't> {}
+
+/// An iterator that yields all non-overlapping capture groups matching a
+/// particular regular expression.
+///
+/// The iterator stops when no more matches can be found.
+///
+/// `'r` is the lifetime of the `Regex` struct and `'t` is the lifetime
+/// of the matched slice.
+pub struct FindCaptures<'r, 't> {
+    regex: &'r Regex,
+    text: &'t [u8],
+    last_end: usize,
+    last_match_end: Option<usize>,
+}
+
+impl<'r, 't> Iterator for FindCaptures<'r, 't> {
+    type Item = Captures<'t>;
+
+    fn next(&mut self) -> Option<Captures<'t>> {
+        let mut region = Region::new();
+        let offset = self.regex.next_match(
+            self.text,
+            &mut region,
+            &mut self.last_end,
+            &mut self.last_match_end,
+        )?;
+        Some(Captures {
+            names: self.regex.inner.name_table(),
+            text: self.text,
+            region,
+            offset,
+        })
+    }
+}
+
+impl<'r, 't> FusedIterator for FindCaptures<'r, 't> {}
+
+/// Yields all slices delimited by a regular expression match.
+///
+/// `'r` is the lifetime of the compiled expression and `'t` is the lifetime
+/// of the slice being split.
+pub struct RegexSplits<'r, 't> {
+    finder: FindMatches<'r, 't>,
+    last: usize,
+}
+
+impl<'r, 't> Iterator for RegexSplits<'r, 't> {
+    type Item = &'t [u8];
+
+    fn next(&mut self) -> Option<&'t [u8]> {
+        let text = self.finder.text;
+        match self.finder.next() {
+            None => {
+                if self.last >= text.len() {
+                    None
+                } else {
+                    let s = &text[self.last..];
+                    self.last = text.len();
+                    Some(s)
+                }
+            }
+            Some((s, e)) => {
+                let matched = &text[self.last..s];
+                self.last = e;
+                Some(matched)
+            }
+        }
+    }
+}
+
+impl<'r, 't> FusedIterator for RegexSplits<'r, 't> {}
+
+/// Yields at most `N` slices delimited by a regular expression match.
+///
+/// The last slice will be whatever remains after splitting.
+///
+/// `'r` is the lifetime of the compiled expression and `'t` is the lifetime
+/// of the slice being split.
+pub struct RegexSplitsN<'r, 't> {
+    splits: RegexSplits<'r, 't>,
+    n: usize,
+}
+
+impl<'r, 't> Iterator for RegexSplitsN<'r, 't> {
+    type Item = &'t [u8];
+
+    fn next(&mut self) -> Option<&'t [u8]> {
+        if self.n == 0 {
+            return None;
+        }
+        self.n -= 1;
+        if self.n == 0 {
+            let text = self.splits.finder.text;
+            Some(&text[self.splits.last..])
+        } else {
+            self.splits.next()
+        }
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (0, Some(self.n))
+    }
+}
+
+impl<'r, 't> FusedIterator for RegexSplitsN<'r, 't> {}
+
+#[cfg(test)]
+mod tests {
+    use super::super::super::{EncodedBytes, Encoding};
+    use super::*;
+
+    fn utf16le() -> Encoding {
+        Encoding::Utf16Le
+    }
+
+    #[test]
+    fn test_bytes_captures() {
+        let regex = Regex::new("e(l+)|(r+)").unwrap();
+        let captures = regex.captures(b"hello").unwrap();
+        assert_eq!(captures.len(), 3);
+        assert_eq!(captures.pos(0), Some((1, 4)));
+        assert_eq!(captures.at(1), Some(&b"ll"[..]));
+        assert_eq!(captures.at(2), None);
+        assert_eq!(captures.offset(), 1);
+        assert_eq!(
+            captures.iter().collect::<Vec<_>>(),
+            vec![Some(&b"ell"[..]), Some(&b"ll"[..]), None]
+        );
+        assert_eq!(
+            captures.iter_pos().collect::<Vec<_>>(),
+            vec![Some((1, 4)), Some((2, 4)), None]
+        );
+    }
+
+    #[test]
+    fn test_bytes_captures_name() {
+        let regex = Regex::new("(?<key>[a-z]+)=(?<val>[^;]*)").unwrap();
+        let captures = regex.captures(b"\xffkey=\xfe\xfd;").unwrap();
+        assert_eq!(captures.name("key"), Some(&b"key"[..]));
+        assert_eq!(&captures["val"], &b"\xfe\xfd"[..]);
+        assert_eq!(captures.name("other"), None);
+        assert_eq!(
+            captures.iter_names().collect::<Vec<_>>(),
+            vec![("key", Some(&b"key"[..])), ("val", Some(&b"\xfe\xfd"[..]))]
+        );
+    }
+
+    #[test]
+    fn test_bytes_captures_outlive_regex() {
+        fn key(text: &[u8]) -> Option<&[u8]> {
+            let regex = Regex::new("(?<key>[a-z]+)=").unwrap();
+            regex.captures(text).and_then(|c| c.name("key"))
+        }
+        assert_eq!(key(b"\xffkey=1"), Some(&b"key"[..]));
+    }
+
+    #[test]
+    fn test_bytes_find_iter_zero_length() {
+        let re = Regex::new(r"\d*").unwrap();
+        let ms = re.find_iter(b"a1bbb2").collect::<Vec<_>>();
+        assert_eq!(ms, vec![(0, 0), (1, 2), (3, 3), (4, 4), (5, 6)]);
+    }
+
+    #[test]
+    fn test_bytes_find_iter_steps_whole_characters() {
+        // An empty pattern matches between each UTF-16 code unit pair,
+        // never in the middle of one.
+        let pattern = [0u8; 0];
+        let re = Regex::with_encoding(EncodedBytes::from_parts(&pattern, utf16le())).unwrap();
+        let text = [b'a', 0, b'b', 0];
+        let ms = re.find_iter(&text).collect::<Vec<_>>();
+        assert_eq!(ms, vec![(0, 0), (2, 2), (4, 4)]);
+
+        // A trailing odd byte is stepped over on its own.
+        let text = [b'a', 0, b'b'];
+        let ms = re.find_iter(&text).collect::<Vec<_>>();
+        assert_eq!(ms, vec![(0, 0), (2, 2), (3, 3)]);
+    }
+
+    #[test]
+    fn test_bytes_captures_iter() {
+        let re = Regex::new(r"(\d)(\d)?").unwrap();
+        let caps = re.captures_iter(b"a12\xffb3").collect::<Vec<_>>();
+        assert_eq!(caps.len(), 2);
+        assert_eq!(caps[0].at(0), Some(&b"12"[..]));
+        assert_eq!(caps[1].at(1), Some(&b"3"[..]));
+        assert_eq!(caps[1].at(2), None);
+        assert_eq!(caps[1].offset(), 5);
+    }
+
+    #[test]
+    fn test_bytes_split() {
+        let re = Regex::new(r"\x00+").unwrap();
+        let fields: Vec<&[u8]> = re.split(b"a\x00\x00b\x00c").collect();
+        assert_eq!(fields, vec![&b"a"[..], &b"b"[..], &b"c"[..]]);
+        let fields: Vec<&[u8]> = re.splitn(b"a\x00\x00b\x00c", 2).collect();
+        assert_eq!(fields, vec![&b"a"[..], &b"b\x00c"[..]]);
+    }
+
+    #[test]
+    fn test_bytes_scan() {
+        let re = Regex::new(r"\d+").unwrap();
+        let found = std::cell::RefCell::new(Vec::new());
+        re.scan(b"1 \xff 22 333", |n, caps| {
+            found.borrow_mut().push((n, caps.at(0).unwrap().to_vec()));
+            true
+        });
+        assert_eq!(
+            found.into_inner(),
+            vec![
+                (0, b"1".to_vec()),
+                (1, b"22".to_vec()),
+                (2, b"333".to_vec())
+            ]
+        );
+    }
+}
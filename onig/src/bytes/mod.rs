@@ -0,0 +1,231 @@
+//! Byte Slice Regexes
+//!
+//! This module contains a `Regex` type which searches byte slices
+//! rather than `str`s. The bytes are interpreted using the encoding
+//! the regex was compiled with, so it can be used to search binary
+//! data, or text in any of the encodings Oniguruma supports.
+//!
+//! The API mirrors the one on the main `onig::Regex` type, with
+//! `&[u8]` in place of `&str` and `Vec<u8>` in place of `String`.
+//!
+//! # Examples
+//!
+//! ```
+//! use onig::bytes::Regex;
+//! use onig::EncodedBytes;
+//!
+//! let regex = Regex::with_encoding(EncodedBytes::ascii(b"\\d+")).unwrap();
+//! let numbers: Vec<&[u8]> = regex
+//!     .find_iter(b"\xff12\x0034")
+//!     .map(|(s, e)| &b"\xff12\x0034"[s..e])
+//!     .collect();
+//! assert_eq!(numbers, vec![&b"12"[..], &b"34"[..]]);
+//! ```
+//!
+//! The contents of a haystack should be valid in the encoding of the
+//! regex. Oniguruma doesn't check this, and searching malformed text
+//! may produce unexpected matches.
+
+use super::{
+    EncodedBytes, EncodedChars, Encoding, Error, RegexOptions, Region, SearchOptions, Syntax,
+};
+
+mod find;
+mod replace;
+
+pub use self::find::{
+    Captures, FindCaptures, FindMatches, RegexSplits, RegexSplitsN, SubCaptures, SubCapturesNames,
+    SubCapturesPos,
+};
+pub use self::replace::Replacer;
+
+/// A Byte Slice Regex
+///
+/// A compiled regular expression which searches byte slices. Each
+/// haystack is interpreted using the encoding this regex was compiled
+/// with.
+#[derive(Debug, Clone, Eq, PartialEq, Hash)]
+pub struct Regex {
+    inner: super::Regex,
+}
+
+impl Regex {
+    /// Create a Regex
+    ///
+    /// Compiles a new UTF-8 regular expression with the default
+    /// options using the ruby syntax. Haystacks searched with it are
+    /// treated as UTF-8.
+    ///
+    /// # Arguments
+    ///
+    /// * `pattern` - The regex pattern to compile
+    pub fn new(pattern: &str) -> Result<Self, Error> {
+        super::Regex::new(pattern).map(Regex::from)
+    }
+
+    /// Create a Regex, Specifying an Encoding
+    ///
+    /// Compiles `pattern`, taking the encoding from the pattern
+    /// buffer. Haystacks searched with it are treated as being in the
+    /// same encoding.
+    ///
+    /// # Arguments
+    ///
+    /// * `pattern` - The regex pattern to compile
+    pub fn with_encoding<T>(pattern: T) -> Result<Self, Error>
+    where
+        T: EncodedChars,
+    {
+        super::Regex::with_encoding(pattern).map(Regex::from)
+    }
+
+    /// Create a new Regex, Specifying Options and Encoding
+    ///
+    /// Compiles `pattern` with the given options and syntax, taking
+    /// the encoding from the pattern buffer.
+    ///
+    /// # Arguments
+    ///
+    ///  * `pattern` - The regex pattern to compile.
+    ///  * `options` - The regex compilation options.
+    ///  * `syntax`  - The syntax which the regex is written in.
+    pub fn with_options_and_encoding<T>(
+        pattern: T,
+        option: RegexOptions,
+        syntax: &Syntax,
+    ) -> Result<Self, Error>
+    where
+        T: EncodedChars,
+    {
+        super::Regex::with_options_and_encoding(pattern, option, syntax).map(Regex::from)
+    }
+
+    /// Get the Underlying `onig::Regex`
+    pub fn as_regex(&self) -> &super::Regex {
+        &self.inner
+    }
+
+    /// Get the Encoding of the Regex
+    pub fn encoding(&self) -> Encoding {
+        self.inner.encoding()
+    }
+
+    /// Get the Number of Capture Groups in this Pattern
+    pub fn captures_len(&self) -> usize {
+        self.inner.captures_len()
+    }
+
+    /// Wrap a haystack in the encoding of this regex
+    fn encoded<'t>(&self, text: &'t [u8]) -> EncodedBytes<'t> {
+        EncodedBytes::from_parts(text, self.encoding())
+    }
+
+    /// Match Bytes
+    ///
+    /// Try to match the regex against the given byte slice, starting
+    /// at the offset `at`. See `onig::Regex::match_with_encoding`.
+    ///
+    /// # Returns
+    ///
+    /// `Some(len)` if the regex matched, with `len` being the number
+    /// of bytes matched. `None` if the regex doesn't match.
+    pub fn match_with_options(
+        &self,
+        text: &[u8],
+        at: usize,
+        options: SearchOptions,
+        region: Option<&mut Region>,
+    ) -> Option<usize> {
+        self.inner
+            .match_with_encoding(self.encoded(text), at, options, region)
+    }
+
+    /// Search pattern in Bytes
+    ///
+    /// Search for the regex in the given byte slice between `from`
+    /// and `to`. See `onig::Regex::search_with_encoding`.
+    ///
+    /// # Returns
+    ///
+    /// `Some(pos)` if the regex matches, where `pos` is the
+    /// byte-position of the start of the match. `None` if the regex
+    /// doesn't match anywhere in `text`.
+    pub fn search_with_options(
+        &self,
+        text: &[u8],
+        from: usize,
+        to: usize,
+        options: SearchOptions,
+        region: Option<&mut Region>,
+    ) -> Option<usize> {
+        self.inner
+            .search_with_encoding(self.encoded(text), from, to, options, region)
+    }
+
+    /// Returns true if and only if the regex matches the whole of
+    /// `text`.
+    pub fn is_match(&self, text: &[u8]) -> bool {
+        self.match_with_options(text, 0, SearchOptions::SEARCH_OPTION_WHOLE_STRING, None)
+            .map(|r| r == text.len())
+            .unwrap_or(false)
+    }
+
+    /// Find a Match in a Byte Slice
+    ///
+    /// Returns the offset of the start and end of the first match of
+    /// the regex within `text`. If no match exists `None` is returned.
+    pub fn find(&self, text: &[u8]) -> Option<(usize, usize)> {
+        self.inner.find_with_encoding(self.encoded(text))
+    }
+}
+
+impl From<super::Regex> for Regex {
+    fn from(inner: super::Regex) -> Self {
+        Regex { inner }
+    }
+}
+
+impl From<Regex> for super::Regex {
+    fn from(regex: Regex) -> Self {
+        regex.inner
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_bytes_regex_create() {
+        Regex::new(r"a \w+ word").unwrap();
+        Regex::with_encoding(EncodedBytes::ascii(b"a+")).unwrap();
+        assert!(Regex::new("\\p{foo}").is_err());
+    }
+
+    #[test]
+    fn test_bytes_is_match() {
+        let regex = Regex::new("he(l+)o").unwrap();
+        assert!(regex.is_match(b"hello"));
+        assert!(!regex.is_match(b"hello\xff"));
+    }
+
+    #[test]
+    fn test_bytes_find_non_utf8() {
+        let regex = Regex::with_encoding(EncodedBytes::ascii(b"b+")).unwrap();
+        assert_eq!(regex.find(b"\xff\xfeabbc"), Some((3, 5)));
+        assert_eq!(regex.find(b"\xff\xfeac"), None);
+    }
+
+    #[test]
+    fn test_bytes_latin1() {
+        let latin1 = Encoding::Iso8859_1;
+        let regex = Regex::with_options_and_encoding(
+            EncodedBytes::from_parts(b"caf\xe9", latin1),
+            RegexOptions::REGEX_OPTION_IGNORECASE,
+            Syntax::default(),
+        )
+        .unwrap();
+        assert_eq!(regex.encoding(), latin1);
+        assert_eq!(regex.find(b"un CAF\xc9 noir"), Some((3, 7)));
+    }
+}
@@ -0,0 +1,111 @@
+use super::{Captures, Regex};
+use std::borrow::Cow;
+
+/// Replacer describes types that can be used to replace matches in a
+/// byte slice.
+///
+/// Implementations are provided for replacement using byte string
+/// literals and `FnMut` callbacks. If this isn't enough a
+/// user-supplied `Replacer` implementation can be provided.
+pub trait Replacer {
+    /// Returns possibly owned bytes that are used to replace the match
+    /// corresponding to the `caps` capture group.
+    fn reg_replace(&mut self, caps: &Captures) -> Cow<'_, [u8]>;
+}
+
+/// Replacement using Literal Byte Slices
+impl Replacer for &[u8] {
+    fn reg_replace(&mut self, _: &Captures) -> Cow<'_, [u8]> {
+        (*self).into()
+    }
+}
+
+/// Replacement using Byte String Literals
+impl<const N: usize> Replacer for &[u8; N] {
+    fn reg_replace(&mut self, _: &Captures) -> Cow<'_, [u8]> {
+        (&self[..]).into()
+    }
+}
+
+/// Replacement using `FnMut` Callbacks
+impl<F> Replacer for F
+where
+    F: FnMut(&Captures) -> Vec<u8>,
+{
+    fn reg_replace(&mut self, caps: &Captures) -> Cow<'_, [u8]> {
+        (*self)(caps).into()
+    }
+}
+
+impl Regex {
+    /// Replaces the leftmost-first match with the replacement provided.
+    /// The replacement can be a byte slice or a function that takes
+    /// the matches `Captures` and returns the replacement bytes.
+    ///
+    /// If no match is found, then a copy of the slice is returned unchanged.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// # use onig::bytes::{Captures, Regex};
+    /// let re = Regex::new(r"(\w+)=(\w+)").unwrap();
+    /// assert_eq!(re.replace(b"\x00key=val", b"-"), b"\x00-");
+    ///
+    /// let swapped = re.replace(b"key=val", |caps: &Captures| {
+    ///     [caps.at(2).unwrap(), caps.at(1).unwrap()].join(&b'=')
+    /// });
+    /// assert_eq!(swapped, b"val=key");
+    /// ```
+    pub fn replace<R: Replacer>(&self, text: &[u8], rep: R) -> Vec<u8> {
+        self.replacen(text, 1, rep)
+    }
+
+    /// Replaces all non-overlapping matches in `text` with the
+    /// replacement provided. This is the same as calling `replacen` with
+    /// `limit` set to `0`.
+    pub fn replace_all<R: Replacer>(&self, text: &[u8], rep: R) -> Vec<u8> {
+        self.replacen(text, 0, rep)
+    }
+
+    /// Replaces at most `limit` non-overlapping matches in `text` with the
+    /// replacement provided. If `limit` is 0, then all non-overlapping matches
+    /// are replaced.
+    pub fn replacen<R: Replacer>(&self, text: &[u8], limit: usize, mut rep: R) -> Vec<u8> {
+        let mut new = Vec::with_capacity(text.len());
+        let mut last_match = 0;
+        for (i, cap) in self.captures_iter(text).enumerate() {
+            if limit > 0 && i >= limit {
+                break;
+            }
+            // unwrap on 0 is OK because captures only reports matches
+            let (s, e) = cap.pos(0).unwrap();
+            new.extend_from_slice(&text[last_match..s]);
+            new.extend_from_slice(&rep.reg_replace(&cap));
+            last_match = e;
+        }
+        new.extend_from_slice(&text[last_match..]);
+        new
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_bytes_replace_literal() {
+        let re = Regex::new("[^01]+").unwrap();
+        assert_eq!(re.replace(b"1078910", b""), b"1010");
+        assert_eq!(re.replace_all(b"1a0b1", &b"_"[..]), b"1_0_1");
+        assert_eq!(re.replacen(b"1a0b1c", 2, b"_"), b"1_0_1c");
+    }
+
+    #[test]
+    fn test_bytes_replace_closure() {
+        let re = Regex::new(r"\d").unwrap();
+        let doubled = re.replace_all(b"\xfe1\xff2", |caps: &Captures| {
+            caps.at(0).unwrap().repeat(2)
+        });
+        assert_eq!(doubled, b"\xfe11\xff22");
+    }
+}
@@ -0,0 +1,548 @@
+//! Callouts
+//!
+//! Callouts let Rust code run at points in a pattern while it is being
+//! matched. A contents callout is written `(?{...})` in the pattern and
+//! invokes the progress or retraction handler registered on the
+//! `MatchParam` used for the search. A named callout is written
+//! `(*NAME)` and invokes a handler registered globally with
+//! `define_callout`, or one of Oniguruma's builtin callouts such as
+//! `(*COUNT)`.
+
+use std::collections::HashMap;
+use std::marker::PhantomData;
+use std::os::raw::{c_int, c_void};
+use std::ptr::null_mut;
+use std::slice;
+use std::str;
+use std::sync::{Arc, Mutex};
+
+use once_cell::sync::Lazy;
+
+use super::match_param::catch_callout_panic;
+use super::{Encoding, Error, MatchParam, Regex, REGEX_NEW_MUTEX};
+
+/// A registered named callout handler.
+type NamedCalloutHandler = Arc<dyn Fn(&CalloutArgs) -> CalloutResult + Send + Sync>;
+
+/// Handlers for the named callouts defined with `define_callout`, keyed
+/// by the name id Oniguruma assigned them.
+static NAMED_CALLOUTS: Lazy<Mutex<HashMap<c_int, NamedCalloutHandler>>> =
+    Lazy::new(|| Mutex::new(HashMap::new()));
+
+/// The Result of a Callout
+///
+/// Returned from a callout handler to tell Oniguruma how to continue
+/// the match.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum CalloutResult {
+    /// Continue matching as though the callout wasn't there.
+    Success,
+    /// Fail at this point and backtrack.
+    Fail,
+    /// Abort the search. The search returns an `Error` with the code
+    /// `ONIG_ABORT`.
+    Error,
+}
+
+impl CalloutResult {
+    /// Get the raw value to return from a callout function.
+    pub(crate) fn as_raw(self) -> c_int {
+        match self {
+            CalloutResult::Success => onig_sys::OnigCalloutResult_ONIG_CALLOUT_SUCCESS as c_int,
+            CalloutResult::Fail => onig_sys::OnigCalloutResult_ONIG_CALLOUT_FAIL as c_int,
+            CalloutResult::Error => onig_sys::ONIG_ABORT,
+        }
+    }
+}
+
+/// When a Callout is Invoked
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum CalloutIn {
+    /// Invoke the callout as the match progresses through it.
+    Progress,
+    /// Invoke the callout when the match backtracks past it.
+    Retraction,
+    /// Invoke the callout in both directions.
+    Both,
+}
+
+impl CalloutIn {
+    /// Get the raw `ONIG_CALLOUT_IN_*` flags for this value.
+    fn as_raw(self) -> c_int {
+        let raw = match self {
+            CalloutIn::Progress => onig_sys::OnigCalloutIn_ONIG_CALLOUT_IN_PROGRESS,
+            CalloutIn::Retraction => onig_sys::OnigCalloutIn_ONIG_CALLOUT_IN_RETRACTION,
+            CalloutIn::Both => {
+                onig_sys::OnigCalloutIn_ONIG_CALLOUT_IN_PROGRESS
+                    | onig_sys::OnigCalloutIn_ONIG_CALLOUT_IN_RETRACTION
+            }
+        };
+        raw as c_int
+    }
+}
+
+/// A Value Stored by a Callout
+///
+/// Callouts can store data in slots which are kept in the
+/// `MatchParam` for a search. The builtin callouts store counts as
+/// `Long` values.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum CalloutValue {
+    /// An integer.
+    Long(i64),
+    /// A code point.
+    Char(u32),
+    /// The bytes of a string.
+    String(Vec<u8>),
+    /// An opaque pointer.
+    Pointer(usize),
+    /// The callout number of a tag.
+    Tag(i32),
+}
+
+impl CalloutValue {
+    /// Convert a raw typed value.
+    // `c_long` is only 32 bits on some platforms.
+    #[allow(clippy::useless_conversion)]
+    unsafe fn from_raw(value_type: onig_sys::OnigType, value: onig_sys::OnigValue) -> Option<Self> {
+        Some(match value_type {
+            onig_sys::OnigType_ONIG_TYPE_LONG => CalloutValue::Long(i64::from(value.l)),
+            onig_sys::OnigType_ONIG_TYPE_CHAR => CalloutValue::Char(value.c),
+            onig_sys::OnigType_ONIG_TYPE_STRING => {
+                CalloutValue::String(bytes_between(value.s.start, value.s.end)?.to_vec())
+            }
+            onig_sys::OnigType_ONIG_TYPE_POINTER => CalloutValue::Pointer(value.p as usize),
+            onig_sys::OnigType_ONIG_TYPE_TAG => CalloutValue::Tag(value.tag),
+            _ => return None,
+        })
+    }
+}
+
+/// Define a Named Callout
+///
+/// Registers `handler` as the callout `(*name)` for patterns in the
+/// given `encoding`. Named callouts are global: once defined, they can
+/// be used by any regex compiled afterwards. Redefining a name
+/// replaces its handler.
+///
+/// Names must start with a letter or `_` and contain only ASCII word
+/// characters. Callouts defined this way don't take arguments.
+///
+/// # Examples
+///
+/// ```
+/// use onig::{define_callout, CalloutIn, CalloutResult, EncodedChars, Regex};
+///
+/// define_callout("ODD_POS", "".encoding(), CalloutIn::Progress, |args| {
+///     if args.position() % 2 == 1 {
+///         CalloutResult::Success
+///     } else {
+///         CalloutResult::Fail
+///     }
+/// })
+/// .unwrap();
+/// let regex = Regex::new(r"a(*ODD_POS)").unwrap();
+/// assert_eq!(regex.find("aaa"), Some((0, 1)));
+/// assert_eq!(regex.find("baa"), Some((2, 3)));
+/// ```
+pub fn define_callout<F>(
+    name: &str,
+    encoding: Encoding,
+    callout_in: CalloutIn,
+    handler: F,
+) -> Result<(), Error>
+where
+    F: Fn(&CalloutArgs) -> CalloutResult + Send + Sync + 'static,
+{
+    let mut name = name.as_bytes().to_vec();
+    let name_range = name.as_mut_ptr_range();
+    let mut callouts = NAMED_CALLOUTS.lock().unwrap();
+    let id = unsafe {
+        let _guard = REGEX_NEW_MUTEX.lock().unwrap();
+        onig_sys::onig_set_callout_of_name(
+            encoding.as_raw(),
+            onig_sys::OnigCalloutType_ONIG_CALLOUT_TYPE_SINGLE,
+            name_range.start,
+            name_range.end,
+            callout_in.as_raw(),
+            Some(named_callout),
+            None,
+            0,
+            null_mut(),
+            0,
+            null_mut(),
+        )
+    };
+    if id < 0 {
+        return Err(Error::from_code(id));
+    }
+    callouts.insert(id, Arc::new(handler));
+    Ok(())
+}
+
+/// The callout function for every named callout defined in Rust.
+unsafe extern "C" fn named_callout(
+    args: *mut onig_sys::OnigCalloutArgs,
+    user_data: *mut c_void,
+) -> c_int {
+    let id = onig_sys::onig_get_name_id_by_callout_args(args);
+    let handler = NAMED_CALLOUTS.lock().unwrap().get(&id).cloned();
+    let run = || match handler {
+        Some(handler) => handler(&CalloutArgs::from_raw(args)),
+        None => CalloutResult::Success,
+    };
+    // Searches which don't go through a `MatchParam`, such as
+    // `Regex::scan`, have no user data to record a panic or an
+    // interrupt in.
+    if user_data.is_null() {
+        return run().as_raw();
+    }
+    catch_callout_panic(user_data, run)
+}
+
+impl Regex {
+    /// Get Callout Data by Tag
+    ///
+    /// Reads a slot of the data stored by the callout tagged `tag`
+    /// during the last search or match which used `match_param`. The
+    /// builtin counting callouts, such as `(*COUNT[x])`, store their
+    /// count in slot `0`.
+    ///
+    /// # Returns
+    ///
+    /// `Ok(Some(value))` if the slot holds a value, `Ok(None)` if the
+    /// callout didn't store anything in the slot during the last
+    /// match attempt. `Err` if there is no callout with the tag.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use onig::{CalloutValue, MatchParam, Regex, SearchOptions};
+    ///
+    /// let regex = Regex::new(r"(?:a(*COUNT[x]))+b").unwrap();
+    /// let mp = MatchParam::default();
+    /// let res = regex.search_with_param("aaab", 0, 4, SearchOptions::SEARCH_OPTION_NONE, None, &mp);
+    /// assert_eq!(res.unwrap(), Some(0));
+    /// assert_eq!(regex.callout_data_by_tag(&mp, "x", 0).unwrap(), Some(CalloutValue::Long(3)));
+    /// ```
+    pub fn callout_data_by_tag(
+        &self,
+        match_param: &MatchParam,
+        tag: &str,
+        slot: usize,
+    ) -> Result<Option<CalloutValue>, Error> {
+        let mut value_type = onig_sys::OnigType_ONIG_TYPE_VOID;
+        let mut value = onig_sys::OnigValue { l: 0 };
+        let r = unsafe {
+            onig_sys::onig_get_callout_data_by_tag(
+                self.raw,
+                match_param.as_raw(),
+                tag.as_ptr(),
+                tag.as_ptr().add(tag.len()),
+                slot as c_int,
+                &mut value_type,
+                &mut value,
+            )
+        };
+        if r < 0 {
+            Err(Error::from_code(r))
+        } else if r == onig_sys::ONIG_NORMAL as c_int {
+            Ok(unsafe { CalloutValue::from_raw(value_type, value) })
+        } else {
+            Ok(None)
+        }
+    }
+}
+
+/// Callout Arguments
+///
+/// Describes the state of the match at the point a callout was
+/// invoked. Positions are byte offsets from the start of the string
+/// being searched.
+pub struct CalloutArgs<'a> {
+    raw: *mut onig_sys::OnigCalloutArgs,
+    _marker: PhantomData<&'a onig_sys::OnigCalloutArgs>,
+}
+
+impl<'a> CalloutArgs<'a> {
+    /// Wrap the raw arguments passed to a callout function.
+    pub(crate) unsafe fn from_raw(raw: *mut onig_sys::OnigCalloutArgs) -> Self {
+        CalloutArgs {
+            raw,
+            _marker: PhantomData,
+        }
+    }
+
+    /// Get the Raw `OnigCalloutArgs` Pointer
+    pub fn as_raw(&self) -> *mut onig_sys::OnigCalloutArgs {
+        self.raw
+    }
+
+    /// The number of the callout within the pattern. Callouts are
+    /// numbered from 1 in the order they appear.
+    pub fn callout_num(&self) -> i32 {
+        unsafe { onig_sys::onig_get_callout_num_by_callout_args(self.raw) }
+    }
+
+    /// Returns true if the callout was invoked while backtracking
+    /// rather than while progressing through the pattern.
+    pub fn is_retraction(&self) -> bool {
+        let callout_in = unsafe { onig_sys::onig_get_callout_in_by_callout_args(self.raw) };
+        callout_in == onig_sys::OnigCalloutIn_ONIG_CALLOUT_IN_RETRACTION
+    }
+
+    /// The contents of a contents callout, i.e. the `...` in `(?{...})`,
+    /// in the encoding of the pattern. `None` for named callouts.
+    pub fn contents(&self) -> Option<&'a [u8]> {
+        unsafe {
+            let start = onig_sys::onig_get_contents_by_callout_args(self.raw);
+            let end = onig_sys::onig_get_contents_end_by_callout_args(self.raw);
+            bytes_between(start, end)
+        }
+    }
+
+    /// The tag given to the callout, e.g. `x` in `(?{...}[x])`, if it
+    /// has one.
+    pub fn tag(&self) -> Option<&'a str> {
+        unsafe {
+            let reg = onig_sys::onig_get_regex_by_callout_args(self.raw);
+            let num = self.callout_num();
+            // `onig_callout_tag_is_exist_at_callout_num` returns 0 for
+            // tagged callouts too in Oniguruma 6.9.8, so check for a tag
+            // start instead. Untagged callouts don't have one.
+            let start = onig_sys::onig_get_callout_tag_start(reg, num);
+            let end = onig_sys::onig_get_callout_tag_end(reg, num);
+            bytes_between(start, end).and_then(|tag| str::from_utf8(tag).ok())
+        }
+    }
+
+    /// The current position of the match.
+    pub fn position(&self) -> usize {
+        unsafe {
+            let string = onig_sys::onig_get_string_by_callout_args(self.raw);
+            let current = onig_sys::onig_get_current_by_callout_args(self.raw);
+            current as usize - string as usize
+        }
+    }
+
+    /// The position the current match attempt started at.
+    pub fn start(&self) -> usize {
+        unsafe {
+            let string = onig_sys::onig_get_string_by_callout_args(self.raw);
+            let start = onig_sys::onig_get_start_by_callout_args(self.raw);
+            start as usize - string as usize
+        }
+    }
+
+    /// The number of retries made so far in this match attempt. This
+    /// is the counter that the retry limit in match is checked against.
+    pub fn retry_counter(&self) -> u64 {
+        unsafe { onig_sys::onig_get_retry_counter_by_callout_args(self.raw) as u64 }
+    }
+
+    /// Returns the start and end positions of capture group `group` at
+    /// this point in the match. Returns `None` if the group hasn't
+    /// captured anything yet or doesn't exist.
+    pub fn capture(&self, group: usize) -> Option<(usize, usize)> {
+        let (mut beg, mut end) = (0, 0);
+        let r = unsafe {
+            onig_sys::onig_get_capture_range_in_callout(
+                self.raw,
+                group as c_int,
+                &mut beg,
+                &mut end,
+            )
+        };
+        if r == onig_sys::ONIG_NORMAL as c_int && beg != onig_sys::ONIG_REGION_NOTPOS {
+            Some((beg as usize, end as usize))
+        } else {
+            None
+        }
+    }
+}
+
+/// Borrow the bytes between two pointers, if they are set.
+unsafe fn bytes_between<'a>(
+    start: *const onig_sys::OnigUChar,
+    end: *const onig_sys::OnigUChar,
+) -> Option<&'a [u8]> {
+    if start.is_null() || end.is_null() {
+        None
+    } else {
+        Some(slice::from_raw_parts(start, end as usize - start as usize))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::*;
+    use std::sync::{Arc, Mutex};
+
+    fn search(regex: &Regex, text: &str, mp: MatchParam) -> Result<Option<usize>, Error> {
+        regex.search_with_param(
+            text,
+            0,
+            text.len(),
+            SearchOptions::SEARCH_OPTION_NONE,
+            None,
+            mp,
+        )
+    }
+
+    #[test]
+    fn test_progress_callout_args() {
+        let regex = Regex::new("(a+)(?{foo}[t])b").unwrap();
+        let seen = Arc::new(Mutex::new(Vec::new()));
+        let mut mp = MatchParam::default();
+        let log = seen.clone();
+        mp.on_progress(move |args| {
+            log.lock().unwrap().push((
+                args.callout_num(),
+                args.contents().map(|c| c.to_vec()),
+                args.tag().map(String::from),
+                args.start(),
+                args.position(),
+                args.capture(1),
+                args.is_retraction(),
+            ));
+            CalloutResult::Success
+        });
+        assert_eq!(search(&regex, "xaab", mp).unwrap(), Some(1));
+        assert_eq!(
+            seen.lock().unwrap()[0],
+            (
+                1,
+                Some(b"foo".to_vec()),
+                Some("t".into()),
+                1,
+                3,
+                Some((1, 3)),
+                false
+            )
+        );
+    }
+
+    #[test]
+    fn test_progress_callout_fail() {
+        let regex = Regex::new("a(?{x})b").unwrap();
+        let mut mp = MatchParam::default();
+        mp.on_progress(|args| {
+            assert_eq!(args.tag(), None);
+            if args.position() < 3 {
+                CalloutResult::Fail
+            } else {
+                CalloutResult::Success
+            }
+        });
+        assert_eq!(search(&regex, "ab ab", mp).unwrap(), Some(3));
+    }
+
+    #[test]
+    fn test_progress_callout_error() {
+        let regex = Regex::new("a(?{x})b").unwrap();
+        let mut mp = MatchParam::default();
+        mp.on_progress(|_| CalloutResult::Error);
+        let e = search(&regex, "ab", mp).unwrap_err();
+        assert_eq!(e.code(), onig_sys::ONIG_ABORT);
+    }
+
+    #[test]
+    fn test_retraction_callout() {
+        let regex = Regex::new("a(?{x}<)c|ab").unwrap();
+        let retractions = Arc::new(Mutex::new(0));
+        let mut mp = MatchParam::default();
+        let count = retractions.clone();
+        mp.on_retraction(move |args| {
+            assert!(args.is_retraction());
+            *count.lock().unwrap() += 1;
+            CalloutResult::Success
+        });
+        assert_eq!(search(&regex, "ab", mp).unwrap(), Some(0));
+        assert_eq!(*retractions.lock().unwrap(), 1);
+    }
+
+    #[test]
+    fn test_define_callout() {
+        let seen = Arc::new(Mutex::new(Vec::new()));
+        let log = seen.clone();
+        define_callout("TEST_LOG", "".encoding(), CalloutIn::Both, move |args| {
+            log.lock().unwrap().push((
+                args.position(),
+                args.is_retraction(),
+                args.contents().is_some(),
+            ));
+            CalloutResult::Success
+        })
+        .unwrap();
+        let regex = Regex::new("a(*TEST_LOG)c|ab").unwrap();
+        assert_eq!(regex.find("ab"), Some((0, 2)));
+        assert_eq!(
+            *seen.lock().unwrap(),
+            vec![(1, false, false), (1, true, false)]
+        );
+    }
+
+    #[test]
+    fn test_define_callout_in_scan() {
+        let calls = Arc::new(Mutex::new(0));
+        let count = calls.clone();
+        define_callout(
+            "TEST_SCAN",
+            "".encoding(),
+            CalloutIn::Progress,
+            move |args| {
+                *count.lock().unwrap() += 1;
+                if args.position() == 5 {
+                    CalloutResult::Fail
+                } else {
+                    CalloutResult::Success
+                }
+            },
+        )
+        .unwrap();
+        let regex = Regex::new("a(*TEST_SCAN)").unwrap();
+        let matches = Mutex::new(Vec::new());
+        regex.scan("a-a-a", |_, caps| {
+            matches.lock().unwrap().push(caps.pos(0).unwrap());
+            true
+        });
+        assert_eq!(*matches.lock().unwrap(), vec![(0, 1), (2, 3)]);
+        assert_eq!(*calls.lock().unwrap(), 3);
+    }
+
+    #[test]
+    fn test_define_callout_invalid_name() {
+        let e = define_callout("1st", "".encoding(), CalloutIn::Progress, |_| {
+            CalloutResult::Success
+        })
+        .unwrap_err();
+        assert_eq!(e.code(), onig_sys::ONIGERR_INVALID_CALLOUT_NAME);
+    }
+
+    #[test]
+    fn test_builtin_callout_data() {
+        let regex = Regex::new("(?:a(*COUNT[x])|b(*COUNT[y]))+(*MAX{2})").unwrap();
+        let mp = MatchParam::default();
+        let res =
+            regex.search_with_param("abaa", 0, 4, SearchOptions::SEARCH_OPTION_NONE, None, &mp);
+        assert_eq!(res.unwrap(), Some(0));
+        assert_eq!(
+            regex.callout_data_by_tag(&mp, "x", 0).unwrap(),
+            Some(CalloutValue::Long(3))
+        );
+        assert_eq!(
+            regex.callout_data_by_tag(&mp, "y", 0).unwrap(),
+            Some(CalloutValue::Long(1))
+        );
+        assert!(regex.callout_data_by_tag(&mp, "z", 0).is_err());
+    }
+
+    #[test]
+    #[should_panic(expected = "from the callout")]
+    fn test_callout_panic_is_propagated() {
+        let regex = Regex::new("a(?{x})").unwrap();
+        let mut mp = MatchParam::default();
+        mp.on_progress(|_| panic!("from the callout"));
+        let _ = search(&regex, "a", mp);
+    }
+}
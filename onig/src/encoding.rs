@@ -0,0 +1,324 @@
+//! Character Encodings
+//!
+//! Contains the definition of `Encoding`, which names one of the
+//! character encodings built in to Oniguruma. Patterns and haystacks
+//! are interpreted using an `Encoding`.
+
+use std::fmt;
+use std::ptr::addr_of_mut;
+use std::str::FromStr;
+
+use super::{Error, ErrorKind};
+
+/// Character Encoding
+///
+/// One of the character encodings Oniguruma is built with. Rust
+/// strings are always `Utf8`; other encodings can be searched with
+/// `EncodedBytes` or the `onig::bytes` module.
+///
+/// # Examples
+///
+/// ```
+/// use onig::{EncodedBytes, Encoding, Regex};
+///
+/// let sjis: Encoding = "Shift_JIS".parse().unwrap();
+/// assert_eq!(sjis, Encoding::Sjis);
+/// assert_eq!(sjis.to_string(), "Shift_JIS");
+///
+/// let regex = Regex::with_encoding(EncodedBytes::from_parts(b"\x83n", sjis)).unwrap();
+/// assert_eq!(regex.encoding(), Encoding::Sjis);
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+#[non_exhaustive]
+pub enum Encoding {
+    /// US-ASCII
+    Ascii,
+    /// ISO-8859-1 (Latin-1)
+    Iso8859_1,
+    /// ISO-8859-2 (Latin-2)
+    Iso8859_2,
+    /// ISO-8859-3 (Latin-3)
+    Iso8859_3,
+    /// ISO-8859-4 (Latin-4)
+    Iso8859_4,
+    /// ISO-8859-5 (Cyrillic)
+    Iso8859_5,
+    /// ISO-8859-6 (Arabic)
+    Iso8859_6,
+    /// ISO-8859-7 (Greek)
+    Iso8859_7,
+    /// ISO-8859-8 (Hebrew)
+    Iso8859_8,
+    /// ISO-8859-9 (Latin-5)
+    Iso8859_9,
+    /// ISO-8859-10 (Latin-6)
+    Iso8859_10,
+    /// ISO-8859-11 (Thai)
+    Iso8859_11,
+    /// ISO-8859-13 (Latin-7)
+    Iso8859_13,
+    /// ISO-8859-14 (Latin-8)
+    Iso8859_14,
+    /// ISO-8859-15 (Latin-9)
+    Iso8859_15,
+    /// ISO-8859-16 (Latin-10)
+    Iso8859_16,
+    /// UTF-8
+    #[default]
+    Utf8,
+    /// UTF-16, big endian
+    Utf16Be,
+    /// UTF-16, little endian
+    Utf16Le,
+    /// UTF-32, big endian
+    Utf32Be,
+    /// UTF-32, little endian
+    Utf32Le,
+    /// EUC-JP
+    EucJp,
+    /// EUC-TW
+    EucTw,
+    /// EUC-KR
+    EucKr,
+    /// EUC-CN
+    EucCn,
+    /// Shift_JIS
+    Sjis,
+    /// Big5
+    Big5,
+    /// GB 18030
+    Gb18030,
+    /// KOI8-R
+    Koi8R,
+    /// Windows-1251
+    Cp1251,
+}
+
+impl Encoding {
+    /// Every supported encoding.
+    pub const ALL: &'static [Encoding] = &[
+        Encoding::Ascii,
+        Encoding::Iso8859_1,
+        Encoding::Iso8859_2,
+        Encoding::Iso8859_3,
+        Encoding::Iso8859_4,
+        Encoding::Iso8859_5,
+        Encoding::Iso8859_6,
+        Encoding::Iso8859_7,
+        Encoding::Iso8859_8,
+        Encoding::Iso8859_9,
+        Encoding::Iso8859_10,
+        Encoding::Iso8859_11,
+        Encoding::Iso8859_13,
+        Encoding::Iso8859_14,
+        Encoding::Iso8859_15,
+        Encoding::Iso8859_16,
+        Encoding::Utf8,
+        Encoding::Utf16Be,
+        Encoding::Utf16Le,
+        Encoding::Utf32Be,
+        Encoding::Utf32Le,
+        Encoding::EucJp,
+        Encoding::EucTw,
+        Encoding::EucKr,
+        Encoding::EucCn,
+        Encoding::Sjis,
+        Encoding::Big5,
+        Encoding::Gb18030,
+        Encoding::Koi8R,
+        Encoding::Cp1251,
+    ];
+
+    /// Get the Name of the Encoding
+    ///
+    /// Returns the name Oniguruma uses for this encoding, such as
+    /// `"UTF-8"` or `"Shift_JIS"`.
+    pub fn name(self) -> &'static str {
+        match self {
+            Encoding::Ascii => "US-ASCII",
+            Encoding::Iso8859_1 => "ISO-8859-1",
+            Encoding::Iso8859_2 => "ISO-8859-2",
+            Encoding::Iso8859_3 => "ISO-8859-3",
+            Encoding::Iso8859_4 => "ISO-8859-4",
+            Encoding::Iso8859_5 => "ISO-8859-5",
+            Encoding::Iso8859_6 => "ISO-8859-6",
+            Encoding::Iso8859_7 => "ISO-8859-7",
+            Encoding::Iso8859_8 => "ISO-8859-8",
+            Encoding::Iso8859_9 => "ISO-8859-9",
+            Encoding::Iso8859_10 => "ISO-8859-10",
+            Encoding::Iso8859_11 => "ISO-8859-11",
+            Encoding::Iso8859_13 => "ISO-8859-13",
+            Encoding::Iso8859_14 => "ISO-8859-14",
+            Encoding::Iso8859_15 => "ISO-8859-15",
+            Encoding::Iso8859_16 => "ISO-8859-16",
+            Encoding::Utf8 => "UTF-8",
+            Encoding::Utf16Be => "UTF-16BE",
+            Encoding::Utf16Le => "UTF-16LE",
+            Encoding::Utf32Be => "UTF-32BE",
+            Encoding::Utf32Le => "UTF-32LE",
+            Encoding::EucJp => "EUC-JP",
+            Encoding::EucTw => "EUC-TW",
+            Encoding::EucKr => "EUC-KR",
+            Encoding::EucCn => "EUC-CN",
+            Encoding::Sjis => "Shift_JIS",
+            Encoding::Big5 => "Big5",
+            Encoding::Gb18030 => "GB18030",
+            Encoding::Koi8R => "KOI8-R",
+            Encoding::Cp1251 => "CP1251",
+        }
+    }
+
+    /// Look up an Encoding by Name
+    ///
+    /// Accepts the names returned by `name`, along with common
+    /// aliases such as `"ascii"`, `"latin1"` and `"sjis"`. Case,
+    /// dashes and underscores are ignored.
+    pub fn from_name(name: &str) -> Option<Encoding> {
+        let key: String = name
+            .chars()
+            .filter(|&c| c != '-' && c != '_' && c != ' ')
+            .map(|c| c.to_ascii_lowercase())
+            .collect();
+        let alias = match key.as_str() {
+            "ascii" => Some(Encoding::Ascii),
+            "latin1" => Some(Encoding::Iso8859_1),
+            "sjis" => Some(Encoding::Sjis),
+            "windows1251" => Some(Encoding::Cp1251),
+            _ => None,
+        };
+        alias.or_else(|| {
+            Encoding::ALL.iter().copied().find(|enc| {
+                enc.name()
+                    .chars()
+                    .filter(|&c| c != '-' && c != '_')
+                    .map(|c| c.to_ascii_lowercase())
+                    .eq(key.chars())
+            })
+        })
+    }
+
+    /// Get the Raw `OnigEncoding` Pointer
+    pub fn as_raw(self) -> onig_sys::OnigEncoding {
+        match self {
+            Encoding::Ascii => addr_of_mut!(onig_sys::OnigEncodingASCII),
+            Encoding::Iso8859_1 => addr_of_mut!(onig_sys::OnigEncodingISO_8859_1),
+            Encoding::Iso8859_2 => addr_of_mut!(onig_sys::OnigEncodingISO_8859_2),
+            Encoding::Iso8859_3 => addr_of_mut!(onig_sys::OnigEncodingISO_8859_3),
+            Encoding::Iso8859_4 => addr_of_mut!(onig_sys::OnigEncodingISO_8859_4),
+            Encoding::Iso8859_5 => addr_of_mut!(onig_sys::OnigEncodingISO_8859_5),
+            Encoding::Iso8859_6 => addr_of_mut!(onig_sys::OnigEncodingISO_8859_6),
+            Encoding::Iso8859_7 => addr_of_mut!(onig_sys::OnigEncodingISO_8859_7),
+            Encoding::Iso8859_8 => addr_of_mut!(onig_sys::OnigEncodingISO_8859_8),
+            Encoding::Iso8859_9 => addr_of_mut!(onig_sys::OnigEncodingISO_8859_9),
+            Encoding::Iso8859_10 => addr_of_mut!(onig_sys::OnigEncodingISO_8859_10),
+            Encoding::Iso8859_11 => addr_of_mut!(onig_sys::OnigEncodingISO_8859_11),
+            Encoding::Iso8859_13 => addr_of_mut!(onig_sys::OnigEncodingISO_8859_13),
+            Encoding::Iso8859_14 => addr_of_mut!(onig_sys::OnigEncodingISO_8859_14),
+            Encoding::Iso8859_15 => addr_of_mut!(onig_sys::OnigEncodingISO_8859_15),
+            Encoding::Iso8859_16 => addr_of_mut!(onig_sys::OnigEncodingISO_8859_16),
+            Encoding::Utf8 => addr_of_mut!(onig_sys::OnigEncodingUTF8),
+            Encoding::Utf16Be => addr_of_mut!(onig_sys::OnigEncodingUTF16_BE),
+            Encoding::Utf16Le => addr_of_mut!(onig_sys::OnigEncodingUTF16_LE),
+            Encoding::Utf32Be => addr_of_mut!(onig_sys::OnigEncodingUTF32_BE),
+            Encoding::Utf32Le => addr_of_mut!(onig_sys::OnigEncodingUTF32_LE),
+            Encoding::EucJp => addr_of_mut!(onig_sys::OnigEncodingEUC_JP),
+            Encoding::EucTw => addr_of_mut!(onig_sys::OnigEncodingEUC_TW),
+            Encoding::EucKr => addr_of_mut!(onig_sys::OnigEncodingEUC_KR),
+            Encoding::EucCn => addr_of_mut!(onig_sys::OnigEncodingEUC_CN),
+            Encoding::Sjis => addr_of_mut!(onig_sys::OnigEncodingSJIS),
+            Encoding::Big5 => addr_of_mut!(onig_sys::OnigEncodingBIG5),
+            Encoding::Gb18030 => addr_of_mut!(onig_sys::OnigEncodingGB18030),
+            Encoding::Koi8R => addr_of_mut!(onig_sys::OnigEncodingKOI8_R),
+            Encoding::Cp1251 => addr_of_mut!(onig_sys::OnigEncodingCP1251),
+        }
+    }
+
+    /// Get the Encoding for a Raw `OnigEncoding` Pointer
+    ///
+    /// Returns `None` if `raw` isn't one of Oniguruma's built in
+    /// encodings.
+    pub fn from_raw(raw: onig_sys::OnigEncoding) -> Option<Encoding> {
+        Encoding::ALL
+            .iter()
+            .copied()
+            .find(|enc| enc.as_raw() == raw)
+    }
+
+    /// The minimum length in bytes of a character in this encoding.
+    pub fn min_char_len(self) -> usize {
+        unsafe { (*self.as_raw()).min_enc_len as usize }
+    }
+
+    /// The maximum length in bytes of a character in this encoding.
+    pub fn max_char_len(self) -> usize {
+        unsafe { (*self.as_raw()).max_enc_len as usize }
+    }
+
+    /// Is this encoding ASCII compatible?
+    ///
+    /// Returns true if ASCII characters are encoded as single bytes
+    /// with their ASCII values. This is true of every encoding except
+    /// UTF-16 and UTF-32.
+    pub fn is_ascii_compatible(self) -> bool {
+        self.min_char_len() == 1
+    }
+}
+
+impl fmt::Display for Encoding {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(self.name())
+    }
+}
+
+impl FromStr for Encoding {
+    type Err = Error;
+
+    fn from_str(name: &str) -> Result<Encoding, Error> {
+        Encoding::from_name(name).ok_or_else(|| {
+            Error::custom(
+                ErrorKind::InvalidArgument,
+                format!("Unknown encoding '{}'", name),
+            )
+        })
+    }
+}
+
+impl From<Encoding> for onig_sys::OnigEncoding {
+    fn from(encoding: Encoding) -> Self {
+        encoding.as_raw()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::ffi::CStr;
+
+    #[test]
+    fn test_encoding_names_match_oniguruma() {
+        for &enc in Encoding::ALL {
+            let name = unsafe { CStr::from_ptr((*enc.as_raw()).name as *const _) };
+            assert_eq!(name.to_str().unwrap(), enc.name());
+            assert_eq!(Encoding::from_name(enc.name()), Some(enc));
+            assert_eq!(Encoding::from_raw(enc.as_raw()), Some(enc));
+        }
+    }
+
+    #[test]
+    fn test_encoding_from_name() {
+        assert_eq!(Encoding::from_name("utf8"), Some(Encoding::Utf8));
+        assert_eq!(Encoding::from_name("utf_16le"), Some(Encoding::Utf16Le));
+        assert_eq!(Encoding::from_name("Latin1"), Some(Encoding::Iso8859_1));
+        assert_eq!(Encoding::from_name("koi8r"), Some(Encoding::Koi8R));
+        assert_eq!(Encoding::from_name("ebcdic"), None);
+        assert!("ebcdic".parse::<Encoding>().is_err());
+    }
+
+    #[test]
+    fn test_encoding_char_len() {
+        assert_eq!(Encoding::Utf8.min_char_len(), 1);
+        assert_eq!(Encoding::Utf8.max_char_len(), 4);
+        assert_eq!(Encoding::Utf16Le.min_char_len(), 2);
+        assert_eq!(Encoding::Utf32Be.min_char_len(), 4);
+    }
+}
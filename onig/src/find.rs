@@ -1,5 +1,8 @@
-use super::{Regex, Region, SearchOptions};
+use super::names::NameTable;
+use super::{EncodedChars, MatchParam, Regex, Region, SearchOptions};
 use std::iter::FusedIterator;
+use std::ops::Index;
+use std::slice;
 
 impl Regex {
     /// Returns the capture groups corresponding to the leftmost-first match
@@ -15,12 +18,100 @@ impl Regex {
             Some(&mut region),
         )
         .map(|pos| Captures {
+            names: self.name_table(),
             text,
             region,
             offset: pos,
         })
     }
 
+    /// Create Capture Locations
+    ///
+    /// Returns an empty set of capture locations sized for this regex,
+    /// to be filled by `captures_read` and `captures_read_at`.
+    pub fn capture_locations(&self) -> CaptureLocations {
+        CaptureLocations {
+            region: Region::with_capacity(self.captures_len() + 1),
+            match_param: self.match_param(),
+        }
+    }
+
+    /// Searches `text` for a match and writes the positions of the
+    /// capture groups into `locs`. This is the same as calling
+    /// `captures_read_at` with a `pos` of `0`.
+    pub fn captures_read(&self, locs: &mut CaptureLocations, text: &str) -> Option<(usize, usize)> {
+        self.captures_read_at(locs, text, 0)
+    }
+
+    /// Read Capture Locations
+    ///
+    /// Searches `text` for a match starting at the byte offset `pos`,
+    /// and writes the positions of the capture groups into `locs`.
+    /// Unlike `captures` this doesn't allocate, so `locs` can be
+    /// reused for many searches in hot loops.
+    ///
+    /// # Returns
+    ///
+    /// The start and end of the overall match, or `None` if the regex
+    /// doesn't match.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// # use onig::Regex;
+    /// let re = Regex::new(r"(\w+)=(\d+)").unwrap();
+    /// let mut locs = re.capture_locations();
+    /// assert_eq!(re.captures_read_at(&mut locs, "a=1 b=22", 3), Some((4, 8)));
+    /// assert_eq!(locs.pos(2), Some((6, 8)));
+    /// ```
+    pub fn captures_read_at(
+        &self,
+        locs: &mut CaptureLocations,
+        text: &str,
+        pos: usize,
+    ) -> Option<(usize, usize)> {
+        let result = self.search_with_match_param(
+            text,
+            pos,
+            text.len(),
+            SearchOptions::SEARCH_OPTION_NONE,
+            Some(&mut locs.region),
+            &locs.match_param,
+        );
+        match result {
+            Ok(r) => r.and_then(|_| locs.pos(0)),
+            Err(e) => panic!("Onig: Regex search error: {}", e.description()),
+        }
+    }
+
+    /// Returns an iterator over all the non-overlapping matches in
+    /// `text`, reusing a single set of `CaptureLocations` for every
+    /// match. The iterator yields the start and end of each match; the
+    /// capture groups of the latest match are available from
+    /// `FindCaptureLocations::locations`.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// # use onig::Regex;
+    /// let re = Regex::new(r"(\w+)=(\d+)").unwrap();
+    /// let mut matches = re.captures_read_iter("a=1 b=22");
+    /// let mut values = Vec::new();
+    /// while matches.next().is_some() {
+    ///     values.push(matches.locations().pos(2));
+    /// }
+    /// assert_eq!(values, vec![Some((2, 3)), Some((6, 8))]);
+    /// ```
+    pub fn captures_read_iter<'r, 't>(&'r self, text: &'t str) -> FindCaptureLocations<'r, 't> {
+        FindCaptureLocations {
+            regex: self,
+            locs: self.capture_locations(),
+            text,
+            last_end: 0,
+            last_match_end: None,
+        }
+    }
+
     /// Returns an iterator for each successive non-overlapping match in `text`,
     /// returning the start and end byte indices with respect to `text`.
     ///
@@ -86,6 +177,68 @@ impl Regex {
         }
     }
 
+    /// Find the Last Match in a String
+    ///
+    /// Searches `text` backwards from its end, and returns the start
+    /// and end of the match which starts furthest to the right. Each
+    /// candidate start is matched forwards as usual, so for `\w+` this
+    /// is the last character of the last word rather than the whole
+    /// word. If no match exists `None` is returned.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// # use onig::Regex;
+    /// let re = Regex::new(r"\d+").unwrap();
+    /// assert_eq!(re.rfind("a12b345c"), Some((6, 7)));
+    /// assert_eq!(Regex::new("b3").unwrap().rfind("a12b345c"), Some((3, 5)));
+    /// ```
+    pub fn rfind(&self, text: &str) -> Option<(usize, usize)> {
+        let mut region = Region::new();
+        self.search_with_options(
+            text,
+            text.len(),
+            0,
+            SearchOptions::SEARCH_OPTION_NONE,
+            Some(&mut region),
+        )
+        .and_then(|_| region.pos(0))
+    }
+
+    /// Returns an iterator over the non-overlapping matches in `text`,
+    /// walking backwards from its end. Each match is the one found by
+    /// `rfind` within the text before the previous match, so it ends at
+    /// or before the start of the previous match.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// # use onig::Regex;
+    /// let re = Regex::new(r"[a-z]\d").unwrap();
+    /// let ms: Vec<_> = re.rfind_iter("a1 b2c3").collect();
+    /// assert_eq!(ms, vec![(5, 7), (3, 5), (0, 2)]);
+    /// ```
+    pub fn rfind_iter<'r, 't>(&'r self, text: &'t str) -> RevFindMatches<'r, 't> {
+        RevFindMatches {
+            regex: self,
+            region: Region::new(),
+            text,
+            next_from: Some(text.len()),
+        }
+    }
+
+    /// Returns an iterator over the non-overlapping capture groups
+    /// matched in `text`, walking backwards from its end. This is
+    /// operationally the same as `rfind_iter` (except it yields
+    /// information about submatches).
+    pub fn rcaptures_iter<'r, 't>(&'r self, text: &'t str) -> RevFindCaptures<'r, 't> {
+        RevFindCaptures {
+            regex: self,
+            text,
+            next_from: Some(text.len()),
+        }
+    }
+
     /// Returns an iterator of substrings of `text` delimited by a match
     /// of the regular expression.
     /// Namely, each element of the iterator corresponds to text that *isn't*
@@ -148,17 +301,34 @@ impl Regex {
         to_search: &str,
         region: &mut Region,
         options: SearchOptions,
+        callback: F,
+    ) -> i32
+    where
+        F: Fn(i32, i32, &Region) -> bool,
+    {
+        self.scan_with_encoding(to_search, region, options, callback)
+    }
+
+    /// Scan the given buffer, capturing into the given region and
+    /// executing a callback for each match. The encoding of `chars`
+    /// must match the encoding of the regex.
+    pub(crate) fn scan_with_encoding<T, F>(
+        &self,
+        chars: T,
+        region: &mut Region,
+        options: SearchOptions,
         mut callback: F,
     ) -> i32
     where
+        T: EncodedChars,
         F: Fn(i32, i32, &Region) -> bool,
     {
         use onig_sys::{onig_scan, OnigRegion};
         use std::os::raw::{c_int, c_void};
 
         // Find the bounds of the string we're searching
-        let start = to_search.as_ptr();
-        let end = to_search[to_search.len()..].as_ptr();
+        let start = chars.start_ptr();
+        let end = chars.limit_ptr();
 
         unsafe extern "C" fn scan_cb<F>(
             i: c_int,
@@ -169,9 +339,11 @@ impl Regex {
         where
             F: Fn(i32, i32, &Region) -> bool,
         {
-            let region = Region::clone_from_raw(r);
+            // `r` is the region passed to `onig_scan` below, so it can
+            // be borrowed rather than copied for each match.
+            let region = &*(r as *const Region);
             let callback = &*(ud as *mut F);
-            if callback(i, j, &region) {
+            if callback(i, j, region) {
                 0
             } else {
                 -1
@@ -200,12 +372,14 @@ impl Regex {
         CB: Fn(i32, Captures<'t>) -> bool,
     {
         let mut region = Region::new();
+        let names = self.name_table();
         self.scan_with_region(
             to_search,
             &mut region,
             SearchOptions::SEARCH_OPTION_NONE,
             |n, s, region| {
                 let captures = Captures {
+                    names: names.clone(),
                     text: to_search,
                     region: region.clone(),
                     offset: s as usize,
@@ -225,9 +399,10 @@ impl Regex {
 /// `'t` is the lifetime of the matched text.
 #[derive(Debug)]
 pub struct Captures<'t> {
-    text: &'t str,
-    region: Region,
-    offset: usize,
+    pub(crate) names: NameTable,
+    pub(crate) text: &'t str,
+    pub(crate) region: Region,
+    pub(crate) offset: usize,
 }
 
 impl<'t> Captures<'t> {
@@ -245,6 +420,39 @@ impl<'t> Captures<'t> {
         self.pos(pos).map(|(beg, end)| &self.text[beg..end])
     }
 
+    /// Returns the start and end positions of the capture group called
+    /// `name`. Returns `None` if there is no group with that name or if
+    /// the group did not match anything.
+    ///
+    /// If more than one group has the same name, the last of them
+    /// which took part in the match is used.
+    pub fn name_pos(&self, name: &str) -> Option<(usize, usize)> {
+        self.names
+            .group(name, &self.region)
+            .and_then(|group| self.pos(group))
+    }
+
+    /// Returns the matched string for the capture group called `name`.
+    /// If there is no group with that name or it didn't match anything,
+    /// then `None` is returned.
+    ///
+    /// If more than one group has the same name, the last of them
+    /// which took part in the match is used.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// # use onig::Regex;
+    /// let re = Regex::new(r"(?<key>\w+)=(?:(?<val>\d+)|(?<val>\w+))").unwrap();
+    /// let caps = re.captures("answer=forty-two").unwrap();
+    /// assert_eq!(caps.name("key"), Some("answer"));
+    /// assert_eq!(caps.name("val"), Some("forty"));
+    /// assert_eq!(caps.name("nope"), None);
+    /// ```
+    pub fn name(&self, name: &str) -> Option<&'t str> {
+        self.name_pos(name).map(|(beg, end)| &self.text[beg..end])
+    }
+
     /// Returns the number of captured groups.
     pub fn len(&self) -> usize {
         self.region.len()
@@ -268,12 +476,63 @@ impl<'t> Captures<'t> {
         SubCapturesPos { idx: 0, caps: self }
     }
 
+    /// Creates an iterator of the named capture groups, yielding the
+    /// name of each group along with the string it matched. Each name
+    /// appears once, even if it is used by more than one group, and the
+    /// names are ordered by the first group which uses them.
+    pub fn iter_names(&'t self) -> SubCapturesNames<'t> {
+        SubCapturesNames {
+            names: self.names.entries().iter(),
+            caps: self,
+        }
+    }
+
     /// Offset of the captures within the given string slice.
     pub fn offset(&self) -> usize {
         self.offset
     }
 }
 
+/// Capture Locations
+///
+/// The positions of the capture groups for a single match, without
+/// the text which was matched. A `CaptureLocations` is created once
+/// with `Regex::capture_locations` and then reused by every call to
+/// `Regex::captures_read_at`, so no allocation happens per match.
+///
+/// The locations keep the match limits of the regex they were created
+/// for, and should be used with searches of that regex.
+#[derive(Debug)]
+pub struct CaptureLocations {
+    region: Region,
+    match_param: MatchParam,
+}
+
+impl CaptureLocations {
+    /// Returns the start and end positions of the Nth capture group.
+    /// Returns `None` if `pos` is not a valid capture group or if the
+    /// capture group did not match anything.
+    pub fn pos(&self, pos: usize) -> Option<(usize, usize)> {
+        self.region.pos(pos)
+    }
+
+    /// Returns the number of capture groups, including the whole match.
+    pub fn len(&self) -> usize {
+        self.region.len()
+    }
+
+    /// Returns true if and only if there are no capture groups, which
+    /// is the case before the first successful search.
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Get the `Region` the locations are stored in.
+    pub fn region(&self) -> &Region {
+        &self.region
+    }
+}
+
 /// An iterator over capture groups for a particular match of a regular
 /// expression.
 ///
@@ -345,6 +604,49 @@ impl<'t> FusedIterator for SubCapturesPos<'t> {}
 
 impl<'t> ExactSizeIterator for SubCapturesPos<'t> {}
 
+/// Get a named group with `caps["name"]`
+///
+/// # Panics
+///
+/// If there is no group with the given name, or the group didn't
+/// match anything.
+impl<'t> Index<&str> for Captures<'t> {
+    type Output = str;
+
+    fn index(&self, name: &str) -> &str {
+        self.name(name)
+            .unwrap_or_else(|| panic!("no group named '{}' matched", name))
+    }
+}
+
+/// An iterator over the named capture groups for a particular match
+/// of a regular expression.
+///
+/// Each item is the name of a group and the string it matched, if
+/// any. `'t` is the lifetime of the matched text.
+pub struct SubCapturesNames<'t> {
+    names: slice::Iter<'t, (Box<str>, Box<[usize]>)>,
+    caps: &'t Captures<'t>,
+}
+
+impl<'t> Iterator for SubCapturesNames<'t> {
+    type Item = (&'t str, Option<&'t str>);
+
+    fn next(&mut self) -> Option<(&'t str, Option<&'t str>)> {
+        self.names
+            .next()
+            .map(|(name, _)| (&**name, self.caps.name(name)))
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.names.size_hint()
+    }
+}
+
+impl<'t> FusedIterator for SubCapturesNames<'t> {}
+
+impl<'t> ExactSizeIterator for SubCapturesNames<'t> {}
+
 /// An iterator over all non-overlapping matches for a particular string.
 ///
 /// The iterator yields a tuple of integers corresponding to the start and end
@@ -444,6 +746,7 @@ impl<'r, 't> Iterator for FindCaptures<'r, 't> {
             self.last_match_end = Some(e);
         }
         Some(Captures {
+            names: self.regex.name_table(),
             text: self.text,
             region,
             offset: r,
@@ -453,6 +756,145 @@ impl<'r, 't> Iterator for FindCaptures<'r, 't> {
 
 impl<'r, 't> FusedIterator for FindCaptures<'r, 't> {}
 
+/// An iterator over all non-overlapping matches for a particular
+/// string, which stores the capture groups of each match in a single
+/// reused `CaptureLocations`.
+///
+/// The iterator yields the start and end of each match. The capture
+/// groups of the most recent match can be read with `locations`.
+///
+/// `'r` is the lifetime of the `Regex` struct and `'t` is the lifetime
+/// of the matched string.
+pub struct FindCaptureLocations<'r, 't> {
+    regex: &'r Regex,
+    locs: CaptureLocations,
+    text: &'t str,
+    last_end: usize,
+    last_match_end: Option<usize>,
+}
+
+impl<'r, 't> FindCaptureLocations<'r, 't> {
+    /// Get the capture locations of the most recent match.
+    pub fn locations(&self) -> &CaptureLocations {
+        &self.locs
+    }
+}
+
+impl<'r, 't> Iterator for FindCaptureLocations<'r, 't> {
+    type Item = (usize, usize);
+
+    fn next(&mut self) -> Option<(usize, usize)> {
+        if self.last_end > self.text.len() {
+            return None;
+        }
+        let (s, e) = self
+            .regex
+            .captures_read_at(&mut self.locs, self.text, self.last_end)?;
+
+        // Don't accept empty matches immediately following the last match.
+        // i.e., no infinite loops please.
+        if e == s && self.last_match_end == Some(e) {
+            self.last_end += self.text[self.last_end..]
+                .chars()
+                .next()
+                .map(|c| c.len_utf8())
+                .unwrap_or(1);
+            return self.next();
+        } else {
+            self.last_end = e;
+            self.last_match_end = Some(e);
+        }
+
+        Some((s, e))
+    }
+}
+
+impl<'r, 't> FusedIterator for FindCaptureLocations<'r, 't> {}
+
+/// An iterator over all non-overlapping matches for a particular
+/// string, starting from its end.
+///
+/// The iterator yields a tuple of integers corresponding to the start
+/// and end of the match. The indices are byte offsets. The iterator
+/// stops when no more matches can be found.
+///
+/// `'r` is the lifetime of the `Regex` struct and `'t` is the lifetime
+/// of the matched string.
+pub struct RevFindMatches<'r, 't> {
+    regex: &'r Regex,
+    region: Region,
+    text: &'t str,
+    next_from: Option<usize>,
+}
+
+impl<'r, 't> Iterator for RevFindMatches<'r, 't> {
+    type Item = (usize, usize);
+
+    fn next(&mut self) -> Option<(usize, usize)> {
+        self.region.clear();
+        let r = rsearch(self.regex, self.text, &mut self.next_from, &mut self.region)?;
+        Some((r, self.region.pos(0).unwrap().1))
+    }
+}
+
+impl<'r, 't> FusedIterator for RevFindMatches<'r, 't> {}
+
+/// An iterator that yields all non-overlapping capture groups matching
+/// a particular regular expression, starting from the end of the
+/// string.
+///
+/// The iterator stops when no more matches can be found.
+///
+/// `'r` is the lifetime of the `Regex` struct and `'t` is the lifetime
+/// of the matched string.
+pub struct RevFindCaptures<'r, 't> {
+    regex: &'r Regex,
+    text: &'t str,
+    next_from: Option<usize>,
+}
+
+impl<'r, 't> Iterator for RevFindCaptures<'r, 't> {
+    type Item = Captures<'t>;
+
+    fn next(&mut self) -> Option<Captures<'t>> {
+        let mut region = Region::new();
+        let r = rsearch(self.regex, self.text, &mut self.next_from, &mut region)?;
+        Some(Captures {
+            names: self.regex.name_table(),
+            text: self.text,
+            region,
+            offset: r,
+        })
+    }
+}
+
+impl<'r, 't> FusedIterator for RevFindCaptures<'r, 't> {}
+
+/// Search backwards from `next_from` and update it for the next search.
+///
+/// A backward search only matches text up to the character after its
+/// starting position. Starting the next search one character before
+/// the start of this match means it can't overlap this one, and an
+/// empty match can't immediately precede it; the mirror image of how
+/// `FindMatches` handles empty matches.
+fn rsearch(
+    regex: &Regex,
+    text: &str,
+    next_from: &mut Option<usize>,
+    region: &mut Region,
+) -> Option<usize> {
+    let from = next_from.take()?;
+    let s = regex.search_with_options(
+        text,
+        from,
+        0,
+        SearchOptions::SEARCH_OPTION_NONE,
+        Some(region),
+    )?;
+    *next_from = text[..s].chars().next_back().map(|c| s - c.len_utf8());
+    Some(s)
+}
+
 /// Yields all substrings delimited by a regular expression match.
 ///
 /// `'r` is the lifetime of the compiled expression and `'t` is the lifetime
@@ -566,6 +1008,97 @@ mod tests {
         assert_eq!(caps.len(), 2);
     }
 
+    #[test]
+    fn test_regex_captures_name() {
+        let regex = Regex::new("(?<foo>he)(?<bar>l+)(?<bar>o)?").unwrap();
+        let captures = regex.captures("hello").unwrap();
+        assert_eq!(captures.name("foo"), Some("he"));
+        assert_eq!(captures.name("bar"), Some("o"));
+        assert_eq!(captures.name_pos("bar"), Some((4, 5)));
+        assert_eq!(captures.name("baz"), None);
+        assert_eq!(&captures["foo"], "he");
+
+        let captures = regex.captures("hell").unwrap();
+        assert_eq!(captures.name("bar"), Some("ll"));
+        assert_eq!(&captures["bar"], "ll");
+    }
+
+    #[test]
+    #[should_panic(expected = "no group named 'baz' matched")]
+    fn test_regex_captures_index_missing_name() {
+        let regex = Regex::new("(?<foo>he)").unwrap();
+        let captures = regex.captures("hello").unwrap();
+        let _ = &captures["baz"];
+    }
+
+    #[test]
+    fn test_regex_subcapturesnames() {
+        let regex = Regex::new("(?<foo>he)|(?<bar>l+)").unwrap();
+        let captures = regex.captures("llo").unwrap();
+        let names = captures.iter_names().collect::<Vec<_>>();
+        assert_eq!(names, vec![("foo", None), ("bar", Some("ll"))]);
+
+        let regex = Regex::new("e(l+)").unwrap();
+        let captures = regex.captures("hello").unwrap();
+        assert_eq!(captures.iter_names().count(), 0);
+    }
+
+    #[test]
+    fn test_captures_outlive_regex() {
+        fn first_word(text: &str) -> Option<&str> {
+            let regex = Regex::new(r"(\w+)").unwrap();
+            regex.captures(text).and_then(|c| c.at(1))
+        }
+        assert_eq!(first_word("hello world"), Some("hello"));
+
+        let captures = {
+            let regex = Regex::new("(?<a>x)|(?<a>y)").unwrap();
+            regex.captures("y").unwrap()
+        };
+        assert_eq!(captures.name("a"), Some("y"));
+        assert_eq!(
+            captures.iter_names().collect::<Vec<_>>(),
+            vec![("a", Some("y"))]
+        );
+    }
+
+    #[test]
+    fn test_captures_read_at() {
+        let regex = Regex::new("e(l+)|(r+)").unwrap();
+        let mut locs = regex.capture_locations();
+        assert!(locs.region().capacity() >= 3);
+        assert_eq!(regex.captures_read(&mut locs, "hello"), Some((1, 4)));
+        assert_eq!(locs.len(), 3);
+        assert_eq!(locs.pos(1), Some((2, 4)));
+        assert_eq!(locs.pos(2), None);
+        assert_eq!(
+            regex.captures_read_at(&mut locs, "hello world", 5),
+            Some((8, 9))
+        );
+        assert_eq!(locs.pos(1), None);
+        assert_eq!(locs.pos(2), Some((8, 9)));
+        assert_eq!(regex.captures_read_at(&mut locs, "hello", 4), None);
+    }
+
+    #[test]
+    fn test_captures_read_iter() {
+        let re = Regex::new(r"(\d)?").unwrap();
+        let mut matches = re.captures_read_iter("a1bb2");
+        let mut groups = Vec::new();
+        while let Some(pos) = matches.next() {
+            groups.push((pos, matches.locations().pos(1)));
+        }
+        assert_eq!(
+            groups,
+            vec![
+                ((0, 0), None),
+                ((1, 2), Some((1, 2))),
+                ((3, 3), None),
+                ((4, 5), Some((4, 5))),
+            ]
+        );
+    }
+
     #[test]
     fn test_find_iter() {
         let re = Regex::new(r"\d+").unwrap();
@@ -573,6 +1106,42 @@ mod tests {
         assert_eq!(ms, vec![(1, 3), (4, 5)]);
     }
 
+    #[test]
+    fn test_rfind() {
+        let re = Regex::new(r"\d+").unwrap();
+        assert_eq!(re.rfind("a12b2"), Some((4, 5)));
+        assert_eq!(re.rfind("abc"), None);
+        assert_eq!(Regex::new("b$").unwrap().rfind("abab"), Some((3, 4)));
+    }
+
+    #[test]
+    fn test_rfind_iter() {
+        let re = Regex::new(r"\d\d").unwrap();
+        let ms = re.rfind_iter("a123b45").collect::<Vec<_>>();
+        assert_eq!(ms, vec![(5, 7), (2, 4)]);
+    }
+
+    #[test]
+    fn test_rfind_iter_zero_length() {
+        let re = Regex::new(r"\d*").unwrap();
+        let ms = re.rfind_iter("aé1").collect::<Vec<_>>();
+        assert_eq!(ms, vec![(4, 4), (3, 4), (1, 1), (0, 0)]);
+        let re = Regex::new(r"x*").unwrap();
+        let ms = re.rfind_iter("ab").collect::<Vec<_>>();
+        assert_eq!(ms, vec![(2, 2), (1, 1), (0, 0)]);
+    }
+
+    #[test]
+    fn test_rcaptures_iter() {
+        let re = Regex::new(r"(?<key>\w)=(?<val>\d)").unwrap();
+        let caps = re.rcaptures_iter("a=1 b=2").collect::<Vec<_>>();
+        assert_eq!(caps.len(), 2);
+        assert_eq!(caps[0].name("key"), Some("b"));
+        assert_eq!(caps[0].pos(2), Some((6, 7)));
+        assert_eq!(caps[1].at(0), Some("a=1"));
+        assert_eq!(caps[1].offset(), 0);
+    }
+
     #[test]
     fn test_find_iter_one_zero_length() {
         let re = Regex::new(r"\d*").unwrap();
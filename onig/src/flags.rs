@@ -1,6 +1,8 @@
 use bitflags::bitflags;
 use std::os::raw::c_uint;
 
+use super::{Error, ErrorKind};
+
 bitflags! {
     /// Regex parsing and compilation options.
     #[derive(PartialEq, Eq, PartialOrd, Ord, Hash, Debug, Clone, Copy)]
@@ -37,6 +39,27 @@ bitflags! {
         /// Named and no-named group captured.
         const REGEX_OPTION_CAPTURE_GROUP
             = onig_sys::ONIG_OPTION_CAPTURE_GROUP;
+        /// Ignore case for ASCII characters only.
+        const REGEX_OPTION_IGNORECASE_IS_ASCII
+            = onig_sys::ONIG_OPTION_IGNORECASE_IS_ASCII;
+        /// `\w`, `\b` and `\p{Word}` only match ASCII.
+        const REGEX_OPTION_WORD_IS_ASCII
+            = onig_sys::ONIG_OPTION_WORD_IS_ASCII;
+        /// `\d`, `\p{Digit}` and `[[:digit:]]` only match ASCII.
+        const REGEX_OPTION_DIGIT_IS_ASCII
+            = onig_sys::ONIG_OPTION_DIGIT_IS_ASCII;
+        /// `\s`, `\p{Space}` and `[[:space:]]` only match ASCII.
+        const REGEX_OPTION_SPACE_IS_ASCII
+            = onig_sys::ONIG_OPTION_SPACE_IS_ASCII;
+        /// POSIX brackets and their `\p{...}` forms only match ASCII.
+        const REGEX_OPTION_POSIX_IS_ASCII
+            = onig_sys::ONIG_OPTION_POSIX_IS_ASCII;
+        /// `\X` and `\y` use extended grapheme cluster boundaries.
+        const REGEX_OPTION_TEXT_SEGMENT_EXTENDED_GRAPHEME_CLUSTER
+            = onig_sys::ONIG_OPTION_TEXT_SEGMENT_EXTENDED_GRAPHEME_CLUSTER;
+        /// `\X` and `\y` use word boundaries.
+        const REGEX_OPTION_TEXT_SEGMENT_WORD
+            = onig_sys::ONIG_OPTION_TEXT_SEGMENT_WORD;
     }
 }
 
@@ -56,6 +79,78 @@ bitflags! {
         /// Try and match the pattern against the whole string.
         const SEARCH_OPTION_WHOLE_STRING
             = onig_sys::ONIG_OPTION_MATCH_WHOLE_STRING;
+        /// Check that the string is valid in its encoding before
+        /// searching it.
+        const SEARCH_OPTION_CHECK_VALIDITY_OF_STRING
+            = onig_sys::ONIG_OPTION_CHECK_VALIDITY_OF_STRING;
+        /// Fill in a POSIX `regmatch_t` array rather than a `Region`.
+        /// Only valid when no `Region` is given.
+        const SEARCH_OPTION_POSIX_REGION
+            = onig_sys::ONIG_OPTION_POSIX_REGION;
+        /// String head isn't considered as begin of string (`\A` fails).
+        const SEARCH_OPTION_NOT_BEGIN_STRING
+            = onig_sys::ONIG_OPTION_NOT_BEGIN_STRING;
+        /// String end isn't considered as end of string (`\z` and `\Z`
+        /// fail).
+        const SEARCH_OPTION_NOT_END_STRING
+            = onig_sys::ONIG_OPTION_NOT_END_STRING;
+        /// The search start isn't considered as the start position of
+        /// the search (`\G` fails).
+        const SEARCH_OPTION_NOT_BEGIN_POSITION
+            = onig_sys::ONIG_OPTION_NOT_BEGIN_POSITION;
+        /// Call the callback set with `onig_set_callback_each_match` for
+        /// each match.
+        const SEARCH_OPTION_CALLBACK_EACH_MATCH
+            = onig_sys::ONIG_OPTION_CALLBACK_EACH_MATCH;
+    }
+}
+
+impl RegexOptions {
+    /// Check that these options can be used to compile a regex.
+    pub(crate) fn check(self) -> Result<(), Error> {
+        if RegexOptions::from_bits(self.bits()).is_none() {
+            return Err(Error::custom(
+                ErrorKind::InvalidArgument,
+                format!(
+                    "Invalid regex options {:#x}: search options can't be used to compile a regex",
+                    self.bits()
+                ),
+            ));
+        }
+        // Oniguruma rejects other invalid combinations itself, but
+        // accepts both text segment options at once.
+        let text_segments = RegexOptions::REGEX_OPTION_TEXT_SEGMENT_EXTENDED_GRAPHEME_CLUSTER
+            | RegexOptions::REGEX_OPTION_TEXT_SEGMENT_WORD;
+        if self.contains(text_segments) {
+            return Err(Error::custom(
+                ErrorKind::InvalidArgument,
+                format!("Invalid combination of regex options: {:?}", text_segments),
+            ));
+        }
+        Ok(())
+    }
+}
+
+impl SearchOptions {
+    /// Check that these options can be used to search, with or without
+    /// a `Region` to fill in.
+    pub(crate) fn check(self, has_region: bool) -> Result<(), Error> {
+        if SearchOptions::from_bits(self.bits()).is_none() {
+            return Err(Error::custom(
+                ErrorKind::InvalidArgument,
+                format!(
+                    "Invalid search options {:#x}: regex options can't be used to search",
+                    self.bits()
+                ),
+            ));
+        }
+        if has_region && self.contains(SearchOptions::SEARCH_OPTION_POSIX_REGION) {
+            return Err(Error::custom(
+                ErrorKind::InvalidArgument,
+                "SEARCH_OPTION_POSIX_REGION can't be used with a Region",
+            ));
+        }
+        Ok(())
     }
 }
 
@@ -69,15 +164,21 @@ bitflags! {
         /// `*`
         const SYNTAX_OPERATOR_ASTERISK_ZERO_INF
             = (onig_sys::ONIG_SYN_OP_ASTERISK_ZERO_INF as u64);
+        /// `\*`
+        const SYNTAX_OPERATOR_ESC_ASTERISK_ZERO_INF
+            = (onig_sys::ONIG_SYN_OP_ESC_ASTERISK_ZERO_INF as u64);
         /// `+`
         const SYNTAX_OPERATOR_PLUS_ONE_INF
             = (onig_sys::ONIG_SYN_OP_PLUS_ONE_INF as u64);
+        /// `\+`
+        const SYNTAX_OPERATOR_ESC_PLUS_ONE_INF
+            = (onig_sys::ONIG_SYN_OP_ESC_PLUS_ONE_INF as u64);
         /// `?`
         const SYNTAX_OPERATOR_QMARK_ZERO_ONE
             = (onig_sys::ONIG_SYN_OP_QMARK_ZERO_ONE as u64);
-    /// ?P
-    const SYNTAX_OPERATOR_QMARK_CAPITAL_P_NAME
-        = (onig_sys::ONIG_SYN_OP2_QMARK_CAPITAL_P_NAME as u64);
+        /// `\?`
+        const SYNTAX_OPERATOR_ESC_QMARK_ZERO_ONE
+            = (onig_sys::ONIG_SYN_OP_ESC_QMARK_ZERO_ONE as u64);
         /// `{lower,upper}`
         const SYNTAX_OPERATOR_BRACE_INTERVAL
             = (onig_sys::ONIG_SYN_OP_BRACE_INTERVAL as u64);
@@ -150,6 +251,9 @@ bitflags! {
         /// Variable meta characters
         const SYNTAX_OPERATOR_VARIABLE_META_CHARACTERS
             = (onig_sys::ONIG_SYN_OP_VARIABLE_META_CHARACTERS as u64);
+        /// `\o{1OOOOOOOOOO}`
+        const SYNTAX_OPERATOR_ESC_O_BRACE_OCTAL
+            = (onig_sys::ONIG_SYN_OP_ESC_O_BRACE_OCTAL as u64);
         /// `\Q...\E`
         const SYNTAX_OPERATOR_ESC_CAPITAL_Q_QUOTE
             = (onig_sys::ONIG_SYN_OP2_ESC_CAPITAL_Q_QUOTE as u64) << 32;
@@ -210,6 +314,39 @@ bitflags! {
         /// `\`
         const SYNTAX_OPERATOR_INEFFECTIVE_ESCAPE
             = (onig_sys::ONIG_SYN_OP2_INEFFECTIVE_ESCAPE as u64) << 32;
+        /// `(?(cond)yes|no)`
+        const SYNTAX_OPERATOR_QMARK_LPAREN_IF_ELSE
+            = (onig_sys::ONIG_SYN_OP2_QMARK_LPAREN_IF_ELSE as u64) << 32;
+        /// `\K`
+        const SYNTAX_OPERATOR_ESC_CAPITAL_K_KEEP
+            = (onig_sys::ONIG_SYN_OP2_ESC_CAPITAL_K_KEEP as u64) << 32;
+        /// `\R`
+        const SYNTAX_OPERATOR_ESC_CAPITAL_R_GENERAL_NEWLINE
+            = (onig_sys::ONIG_SYN_OP2_ESC_CAPITAL_R_GENERAL_NEWLINE as u64) << 32;
+        /// `\N, \O`
+        const SYNTAX_OPERATOR_ESC_CAPITAL_N_O_SUPER_DOT
+            = (onig_sys::ONIG_SYN_OP2_ESC_CAPITAL_N_O_SUPER_DOT as u64) << 32;
+        /// `(?~...)`
+        const SYNTAX_OPERATOR_QMARK_TILDE_ABSENT_GROUP
+            = (onig_sys::ONIG_SYN_OP2_QMARK_TILDE_ABSENT_GROUP as u64) << 32;
+        /// `\X, \y, \Y`
+        const SYNTAX_OPERATOR_ESC_X_Y_TEXT_SEGMENT
+            = (onig_sys::ONIG_SYN_OP2_ESC_X_Y_TEXT_SEGMENT as u64) << 32;
+        /// `(?R), (?&name)...`
+        const SYNTAX_OPERATOR_QMARK_PERL_SUBEXP_CALL
+            = (onig_sys::ONIG_SYN_OP2_QMARK_PERL_SUBEXP_CALL as u64) << 32;
+        /// `(?{...}), (?{{...}})`
+        const SYNTAX_OPERATOR_QMARK_BRACE_CALLOUT_CONTENTS
+            = (onig_sys::ONIG_SYN_OP2_QMARK_BRACE_CALLOUT_CONTENTS as u64) << 32;
+        /// `(*name), (*name{args})`
+        const SYNTAX_OPERATOR_ASTERISK_CALLOUT_NAME
+            = (onig_sys::ONIG_SYN_OP2_ASTERISK_CALLOUT_NAME as u64) << 32;
+        /// `(?imxWDSPy), (?-imxWDSP)`
+        const SYNTAX_OPERATOR_OPTION_ONIGURUMA
+            = (onig_sys::ONIG_SYN_OP2_OPTION_ONIGURUMA as u64) << 32;
+        /// `(?P<name>...), (?P=name)`
+        const SYNTAX_OPERATOR_QMARK_CAPITAL_P_NAME
+            = (onig_sys::ONIG_SYN_OP2_QMARK_CAPITAL_P_NAME as u64) << 32;
     }
 }
 
@@ -238,6 +375,15 @@ bitflags! {
         /// `(?<=a|bc)`
         const SYNTAX_BEHAVIOR_DIFFERENT_LEN_ALT_LOOK_BEHIND
             = onig_sys::ONIG_SYN_DIFFERENT_LEN_ALT_LOOK_BEHIND;
+        /// `(?<=a+|b)`
+        const SYNTAX_BEHAVIOR_VARIABLE_LEN_LOOK_BEHIND
+            = onig_sys::ONIG_SYN_VARIABLE_LEN_LOOK_BEHIND;
+        /// `\Z` only matches at the end, as in Python
+        const SYNTAX_BEHAVIOR_PYTHON
+            = onig_sys::ONIG_SYN_PYTHON;
+        /// `(?C), (?I), (?L)` at the start of the pattern
+        const SYNTAX_BEHAVIOR_WHOLE_OPTIONS
+            = onig_sys::ONIG_SYN_WHOLE_OPTIONS;
         /// See Oniguruma documenation
         const SYNTAX_BEHAVIOR_CAPTURE_ONLY_NAMED_GROUP
             = onig_sys::ONIG_SYN_CAPTURE_ONLY_NAMED_GROUP;
@@ -247,12 +393,18 @@ bitflags! {
         /// `a{n}?=(?:a{n})?`
         const SYNTAX_BEHAVIOR_FIXED_INTERVAL_IS_GREEDY_ONLY
             = onig_sys::ONIG_SYN_FIXED_INTERVAL_IS_GREEDY_ONLY;
+        /// `a(?i)b|c` is `a(?i:b)|(?i:c)`
+        const SYNTAX_BEHAVIOR_ISOLATED_OPTION_CONTINUE_BRANCH
+            = onig_sys::ONIG_SYN_ISOLATED_OPTION_CONTINUE_BRANCH;
         /// `[^...]`
         const SYNTAX_BEHAVIOR_NOT_NEWLINE_IN_NEGATIVE_CC
             = onig_sys::ONIG_SYN_NOT_NEWLINE_IN_NEGATIVE_CC;
         /// `[..\w..] etc..`
         const SYNTAX_BEHAVIOR_BACKSLASH_ESCAPE_IN_CC
             = onig_sys::ONIG_SYN_BACKSLASH_ESCAPE_IN_CC;
+        /// `[b-a]`
+        const SYNTAX_BEHAVIOR_ALLOW_EMPTY_RANGE_IN_CC
+            = onig_sys::ONIG_SYN_ALLOW_EMPTY_RANGE_IN_CC;
         /// `[0-9-a]=[0-9\-a]`
         const SYNTAX_BEHAVIOR_ALLOW_DOUBLE_RANGE_OP_IN_CC
             = onig_sys::ONIG_SYN_ALLOW_DOUBLE_RANGE_OP_IN_CC;
@@ -262,6 +414,37 @@ bitflags! {
         /// `(?:a*)+`
         const SYNTAX_BEHAVIOR_WARN_REDUNDANT_NESTED_REPEAT
             = onig_sys::ONIG_SYN_WARN_REDUNDANT_NESTED_REPEAT;
+        /// `[a-\x{7fffffff}]`
+        const SYNTAX_BEHAVIOR_ALLOW_INVALID_CODE_END_OF_RANGE_IN_CC
+            = onig_sys::ONIG_SYN_ALLOW_INVALID_CODE_END_OF_RANGE_IN_CC;
+        /// `^, $` anywhere. Not implemented by Oniguruma
+        const SYNTAX_BEHAVIOR_CONTEXT_INDEP_ANCHORS
+            = onig_sys::ONIG_SYN_CONTEXT_INDEP_ANCHORS;
+    }
+}
+
+bitflags! {
+    /// Case folding behaviour used by `REGEX_OPTION_IGNORECASE`.
+    #[derive(PartialEq, Eq, PartialOrd, Ord, Hash, Debug, Clone, Copy)]
+    pub struct CaseFoldFlags: onig_sys::OnigCaseFoldType {
+        /// Only fold ASCII characters.
+        const CASE_FOLD_ASCII_ONLY
+            = onig_sys::ONIGENC_CASE_FOLD_ASCII_ONLY;
+        /// Use the Turkish and Azeri rules for dotted and dotless `i`.
+        ///
+        /// `I` folds to `ı` and `İ` to `i`. Only has an effect if
+        /// Oniguruma was built with `USE_UNICODE_CASE_FOLD_TURKISH_AZERI`.
+        /// Enable the `case-fold-turkish-azeri` feature to build the
+        /// bundled Oniguruma with it.
+        const CASE_FOLD_TURKISH_AZERI
+            = onig_sys::ONIGENC_CASE_FOLD_TURKISH_AZERI;
+        /// Allow a single character to fold to several characters,
+        /// e.g. `ß` matching `ss`.
+        const CASE_FOLD_MULTI_CHAR
+            = onig_sys::INTERNAL_ONIGENC_CASE_FOLD_MULTI_CHAR;
+        /// Oniguruma's default case folding (`ONIGENC_CASE_FOLD_MIN`).
+        const CASE_FOLD_MIN
+            = onig_sys::ONIGENC_CASE_FOLD_MIN;
     }
 }
 
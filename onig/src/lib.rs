@@ -89,33 +89,54 @@
 
 use once_cell::sync::Lazy;
 
+pub mod bytes;
+
 mod buffers;
+mod builder;
+mod callout;
+mod encoding;
 mod find;
 mod flags;
+mod limits;
 mod match_param;
 mod names;
 mod region;
+mod regset;
 mod replace;
+mod stream;
 mod syntax;
 mod tree;
 mod utils;
+mod warnings;
 
 #[cfg(feature = "std-pattern")]
 mod pattern;
 
 // re-export the onig types publically
-pub use crate::buffers::{EncodedBytes, EncodedChars};
+pub use crate::buffers::{CodeUnit, EncodedBytes, EncodedChars, EncodedUnits};
+pub use crate::builder::RegexBuilder;
+pub use crate::callout::{define_callout, CalloutArgs, CalloutIn, CalloutResult, CalloutValue};
+pub use crate::encoding::Encoding;
 pub use crate::find::{
-    Captures, FindCaptures, FindMatches, RegexSplits, RegexSplitsN, SubCaptures, SubCapturesPos,
+    CaptureLocations, Captures, FindCaptureLocations, FindCaptures, FindMatches, RegexSplits,
+    RegexSplitsN, RevFindCaptures, RevFindMatches, SubCaptures, SubCapturesNames, SubCapturesPos,
 };
 pub use crate::flags::*;
+pub use crate::limits::Limits;
 pub use crate::match_param::MatchParam;
 pub use crate::region::Region;
-pub use crate::replace::Replacer;
+pub use crate::regset::{RegSetLead, RegexSet};
+pub use crate::replace::{NoExpand, Replacer, Template};
+pub use crate::stream::{StreamCaptures, StreamSearcher};
 pub use crate::syntax::{MetaChar, Syntax};
 pub use crate::tree::{CaptureTreeNode, CaptureTreeNodeIter};
 pub use crate::utils::{copyright, define_user_property, version};
+pub use crate::warnings::{clear_warn_handler, set_warn_handler, Warning};
 
+use crate::names::NameTable;
+use std::borrow::Borrow;
+use std::hash::{Hash, Hasher};
+use std::ops::Range;
 use std::os::raw::c_int;
 use std::ptr::{null, null_mut};
 use std::sync::Mutex;
@@ -124,39 +145,182 @@ use std::{error, fmt, str};
 #[derive(Debug)]
 enum ErrorData {
     OnigError(c_int),
-    Custom,
+    Custom(ErrorKind),
+    DeadlineExceeded,
+    Cancelled,
 }
 
 /// This struture represents an error from the underlying Oniguruma libray.
 pub struct Error {
     data: ErrorData,
     description: String,
+    pattern: Option<Vec<u8>>,
+    span: Option<Range<usize>>,
+}
+
+/// The Kind of an Error
+///
+/// A broad category for an `Error`, so that callers can react to
+/// errors without comparing `Error::code` against the `ONIGERR_*`
+/// constants. New kinds may be added in future releases.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[non_exhaustive]
+pub enum ErrorKind {
+    /// The pattern is not valid in the syntax it was compiled with.
+    Syntax,
+    /// The match stack limit was exceeded.
+    MatchStackLimitExceeded,
+    /// The retry limit of a single match attempt was exceeded.
+    RetryLimitInMatchExceeded,
+    /// The retry limit of a whole search was exceeded.
+    RetryLimitInSearchExceeded,
+    /// The limit on subexpression calls in a search was exceeded.
+    SubexpCallLimitInSearchExceeded,
+    /// The pattern is nested deeper than the parse depth limit.
+    ParseDepthLimitExceeded,
+    /// The pattern has more capture groups than the capture limit.
+    CaptureNumLimitExceeded,
+    /// The encodings of a pattern, regex or haystack don't match, or an
+    /// encoding doesn't support the requested operation.
+    EncodingMismatch,
+    /// An argument, such as an offset or a combination of options, is
+    /// invalid.
+    InvalidArgument,
+    /// The search was aborted by a callout.
+    Aborted,
+    /// The search was stopped by the deadline set with
+    /// `MatchParam::set_deadline`.
+    DeadlineExceeded,
+    /// The search was stopped by the flag set with
+    /// `MatchParam::set_cancel_flag`.
+    Cancelled,
+    /// Oniguruma ran out of memory.
+    Memory,
+    /// An internal error in Oniguruma.
+    Internal,
+    /// Any other error.
+    Other,
+}
+
+impl ErrorKind {
+    /// Get the kind of an Oniguruma error code.
+    fn from_code(code: c_int) -> Self {
+        match code {
+            onig_sys::ONIG_ABORT => ErrorKind::Aborted,
+            onig_sys::ONIGERR_MEMORY => ErrorKind::Memory,
+            onig_sys::ONIGERR_MATCH_STACK_LIMIT_OVER => ErrorKind::MatchStackLimitExceeded,
+            onig_sys::ONIGERR_RETRY_LIMIT_IN_MATCH_OVER => ErrorKind::RetryLimitInMatchExceeded,
+            onig_sys::ONIGERR_RETRY_LIMIT_IN_SEARCH_OVER => ErrorKind::RetryLimitInSearchExceeded,
+            onig_sys::ONIGERR_SUBEXP_CALL_LIMIT_IN_SEARCH_OVER => {
+                ErrorKind::SubexpCallLimitInSearchExceeded
+            }
+            onig_sys::ONIGERR_PARSE_DEPTH_LIMIT_OVER => ErrorKind::ParseDepthLimitExceeded,
+            onig_sys::ONIGERR_TOO_MANY_CAPTURES => ErrorKind::CaptureNumLimitExceeded,
+            onig_sys::ONIGERR_DEFAULT_ENCODING_IS_NOT_SET
+            | onig_sys::ONIGERR_SPECIFIED_ENCODING_CANT_CONVERT_TO_WIDE_CHAR
+            | onig_sys::ONIGERR_NOT_SUPPORTED_ENCODING_COMBINATION => ErrorKind::EncodingMismatch,
+            onig_sys::ONIGERR_INVALID_ARGUMENT
+            | onig_sys::ONIGERR_INVALID_COMBINATION_OF_OPTIONS => ErrorKind::InvalidArgument,
+            onig_sys::ONIGERR_TYPE_BUG
+            | onig_sys::ONIGERR_PARSER_BUG
+            | onig_sys::ONIGERR_STACK_BUG
+            | onig_sys::ONIGERR_UNDEFINED_BYTECODE
+            | onig_sys::ONIGERR_UNEXPECTED_BYTECODE
+            | onig_sys::ONIGERR_FAIL_TO_INITIALIZE
+            | onig_sys::ONIGERR_LIBRARY_IS_NOT_INITIALIZED => ErrorKind::Internal,
+            // Errors in the pattern itself
+            -299..=-100
+            | onig_sys::ONIGERR_INVALID_CODE_POINT_VALUE
+            | onig_sys::ONIGERR_TOO_BIG_WIDE_CHAR_VALUE
+            | onig_sys::ONIGERR_TOO_LONG_PROPERTY_NAME
+            | onig_sys::ONIGERR_VERY_INEFFICIENT_PATTERN => ErrorKind::Syntax,
+            _ => ErrorKind::Other,
+        }
+    }
+
+    /// Returns true if this kind is one of the engine limits, which
+    /// can be adjusted with `Limits` or `MatchParam`, being exceeded.
+    pub fn is_limit_exceeded(self) -> bool {
+        matches!(
+            self,
+            ErrorKind::MatchStackLimitExceeded
+                | ErrorKind::RetryLimitInMatchExceeded
+                | ErrorKind::RetryLimitInSearchExceeded
+                | ErrorKind::SubexpCallLimitInSearchExceeded
+                | ErrorKind::ParseDepthLimitExceeded
+                | ErrorKind::CaptureNumLimitExceeded
+        )
+    }
 }
 
 /// This struct is a wrapper around an Oniguruma regular expression
 /// pointer. This represents a compiled regex which can be used in
 /// search and match operations.
-#[derive(Debug, Eq, PartialEq)]
+///
+/// A `Regex` keeps a copy of the pattern and syntax it was compiled
+/// from, so a custom `Syntax` doesn't need to outlive it. Two
+/// regexes are equal if they were compiled from the same pattern with
+/// the same options, syntax, encoding and case folding. Cloning a
+/// regex compiles it again from these inputs.
 pub struct Regex {
     raw: onig_sys::OnigRegex,
+    pattern: Vec<u8>,
+    syntax: Box<Syntax>,
+    // The options and case fold flags as they were passed in, before
+    // Oniguruma adds the syntax's options to them.
+    compile_options: RegexOptions,
+    compile_case_fold: CaseFoldFlags,
+    retry_limit_in_match: Option<u32>,
+    match_stack_limit: Option<u32>,
+    names: NameTable,
 }
 
 unsafe impl Send for Regex {}
 unsafe impl Sync for Regex {}
 
 impl Error {
-    fn from_code_and_info(code: c_int, info: &onig_sys::OnigErrorInfo) -> Self {
-        Error::new(code, info)
+    fn from_code_and_info(code: c_int, info: &onig_sys::OnigErrorInfo, pattern: &[u8]) -> Self {
+        let mut error = Error::new(code, info);
+        // Oniguruma points `par` at the offending part of the pattern for
+        // the errors it reports one for.
+        let start = pattern.as_ptr() as usize;
+        let (par, par_end) = (info.par as usize, info.par_end as usize);
+        if !info.par.is_null() && start <= par && par <= par_end && par_end <= start + pattern.len()
+        {
+            error.span = Some(par - start..par_end - start);
+        }
+        error.pattern = Some(pattern.to_vec());
+        error
     }
 
     fn from_code(code: c_int) -> Self {
         Error::new(code, null())
     }
 
-    fn custom<T: Into<String>>(message: T) -> Self {
+    fn custom<T: Into<String>>(kind: ErrorKind, message: T) -> Self {
         Error {
-            data: ErrorData::Custom,
+            data: ErrorData::Custom(kind),
             description: message.into(),
+            pattern: None,
+            span: None,
+        }
+    }
+
+    fn deadline_exceeded() -> Self {
+        Error {
+            data: ErrorData::DeadlineExceeded,
+            description: "search deadline exceeded".into(),
+            pattern: None,
+            span: None,
+        }
+    }
+
+    fn cancelled() -> Self {
+        Error {
+            data: ErrorData::Cancelled,
+            description: "search cancelled".into(),
+            pattern: None,
+            span: None,
         }
     }
 
@@ -166,11 +330,16 @@ impl Error {
         let description = if let Ok(description) = str::from_utf8(&buff[..len as usize]) {
             description
         } else {
-            return Self::custom("Onig error string was invalid UTF-8");
+            return Self::custom(
+                ErrorKind::from_code(code),
+                "Onig error string was invalid UTF-8",
+            );
         };
         Error {
             data: ErrorData::OnigError(code),
             description: description.to_owned(),
+            pattern: None,
+            span: None,
         }
     }
 
@@ -182,10 +351,93 @@ impl Error {
         }
     }
 
+    /// Return the kind of this error.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use onig::{ErrorKind, Regex};
+    ///
+    /// let e = Regex::new("a)").unwrap_err();
+    /// assert_eq!(e.kind(), ErrorKind::Syntax);
+    /// ```
+    pub fn kind(&self) -> ErrorKind {
+        match self.data {
+            ErrorData::OnigError(code) => ErrorKind::from_code(code),
+            ErrorData::Custom(kind) => kind,
+            ErrorData::DeadlineExceeded => ErrorKind::DeadlineExceeded,
+            ErrorData::Cancelled => ErrorKind::Cancelled,
+        }
+    }
+
     /// Return error description provided by Oniguruma engine.
     pub fn description(&self) -> &str {
         &self.description
     }
+
+    /// Return the pattern which failed to compile, if this is a
+    /// compile error.
+    pub fn pattern(&self) -> Option<&[u8]> {
+        self.pattern.as_deref()
+    }
+
+    /// Return the byte range of the offending part of the pattern
+    ///
+    /// Oniguruma only reports a location for some compile errors, such
+    /// as invalid group or property names. For other errors this is
+    /// `None`.
+    pub fn span(&self) -> Option<Range<usize>> {
+        self.span.clone()
+    }
+
+    /// Render a Diagnostic for a Compile Error
+    ///
+    /// Returns the line of the pattern containing the offending part,
+    /// with a second line of carets underneath it. Returns `None` if
+    /// the error has no `span`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use onig::Regex;
+    ///
+    /// let e = Regex::new("(?<1st>x)").unwrap_err();
+    /// assert_eq!(e.span(), Some(3..6));
+    /// assert_eq!(e.diagnostic().unwrap(), "(?<1st>x)\n   ^^^");
+    /// ```
+    pub fn diagnostic(&self) -> Option<String> {
+        let pattern = self.pattern.as_deref()?;
+        let span = self.span.clone()?;
+        let line_start = pattern[..span.start]
+            .iter()
+            .rposition(|&b| b == b'\n')
+            .map_or(0, |i| i + 1);
+        let line_end = pattern[span.start..]
+            .iter()
+            .position(|&b| b == b'\n')
+            .map_or(pattern.len(), |i| span.start + i);
+        let prefix = String::from_utf8_lossy(&pattern[line_start..span.start]);
+        let token = String::from_utf8_lossy(&pattern[span.start..span.end.min(line_end)]);
+
+        let mut rendered = String::from_utf8_lossy(&pattern[line_start..line_end]).into_owned();
+        rendered.push('\n');
+        // Keep tabs so that the carets line up with the pattern.
+        rendered.extend(prefix.chars().map(|c| if c == '\t' { '\t' } else { ' ' }));
+        rendered.extend(std::iter::repeat('^').take(token.chars().count().max(1)));
+        Some(rendered)
+    }
+
+    /// Returns true if the search was stopped because the deadline set
+    /// with `MatchParam::set_deadline` passed.
+    pub fn is_deadline_exceeded(&self) -> bool {
+        matches!(self.data, ErrorData::DeadlineExceeded)
+    }
+
+    /// Returns true if the search was stopped by the cancellation flag
+    /// set with `MatchParam::set_cancel_flag`.
+    pub fn is_cancelled(&self) -> bool {
+        matches!(self.data, ErrorData::Cancelled)
+    }
 }
 
 impl error::Error for Error {
@@ -266,7 +518,7 @@ impl Regex {
     /// Attempts to compile a pattern into a new `Regex` instance.
     /// Once compiled, it can be used repeatedly to search in a string. If an
     /// invalid expression is given, then an error is returned.
-    /// See [`onig_sys::onig_new`][regex_new] for more information.
+    /// See [`onig_sys::onig_new_deluxe`][regex_new] for more information.
     ///
     /// # Arguments
     ///
@@ -284,7 +536,7 @@ impl Regex {
     /// assert!(r.is_ok());
     /// ```
     ///
-    /// [regex_new]: ./onig_sys/fn.onig_new.html
+    /// [regex_new]: ./onig_sys/fn.onig_new_deluxe.html
     pub fn with_options(
         pattern: &str,
         option: RegexOptions,
@@ -299,9 +551,9 @@ impl Regex {
     /// instance. Instead of assuming UTF-8 as the encoding scheme the
     /// encoding is inferred from the `pattern` buffer. If the regex
     /// fails to compile the returned `Error` value from
-    /// [`onig_new`][regex_new] contains more information.
+    /// [`onig_new_deluxe`][regex_new] contains more information.
     ///
-    /// [regex_new]: ./onig_sys/fn.onig_new.html
+    /// [regex_new]: ./onig_sys/fn.onig_new_deluxe.html
     ///
     /// # Arguments
     ///
@@ -326,11 +578,87 @@ impl Regex {
     where
         T: EncodedChars,
     {
+        let target = pattern.encoding();
+        Regex::compile(pattern, target, option, syntax, Regex::default_case_fold())
+    }
+
+    /// Escape Text
+    ///
+    /// Returns a pattern which matches `text` literally when compiled
+    /// with the default syntax, as `Regex::new` does. Use
+    /// `Syntax::escape` for patterns in other syntaxes.
+    ///
+    /// # Arguments
+    ///
+    ///  * `text` - The text to match literally
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use onig::Regex;
+    ///
+    /// let pattern = format!("^{}$", Regex::escape("(1+1)*2 = 4?"));
+    /// assert_eq!(pattern, r"^\(1\+1\)\*2\ =\ 4\?$");
+    /// assert!(Regex::new(&pattern).unwrap().is_match("(1+1)*2 = 4?"));
+    /// ```
+    pub fn escape(text: &str) -> String {
+        Syntax::default().escape(text)
+    }
+
+    /// Compile a Regex
+    ///
+    /// Shared implementation for the `Regex` constructors and
+    /// `RegexBuilder`. Compiles `pattern` with `onig_new_deluxe` so
+    /// that the case fold flags can be chosen per regex.
+    ///
+    /// Oniguruma only accepts a `target` encoding which is the same as
+    /// the pattern's. An ASCII pattern is identical in every ASCII
+    /// compatible encoding though, so it is compiled as `target`
+    /// directly. Any other combination is rejected by Oniguruma with
+    /// `ONIGERR_NOT_SUPPORTED_ENCODING_COMBINATION`.
+    pub(crate) fn compile<T>(
+        pattern: T,
+        target: Encoding,
+        option: RegexOptions,
+        syntax: &Syntax,
+        case_fold: CaseFoldFlags,
+    ) -> Result<Self, Error>
+    where
+        T: EncodedChars,
+    {
+        option.check()?;
+
         // Convert the rust types to those required for the call to
-        // `onig_new`.
+        // `onig_new_deluxe`.
         let mut reg: onig_sys::OnigRegex = null_mut();
         let reg_ptr = &mut reg as *mut onig_sys::OnigRegex;
 
+        let bytes = unsafe { std::slice::from_raw_parts(pattern.start_ptr(), pattern.len()) };
+        let pattern_enc = match pattern.encoding() {
+            enc if enc != target
+                && enc.is_ascii_compatible()
+                && target.is_ascii_compatible()
+                && bytes.is_ascii() =>
+            {
+                target
+            }
+            enc => enc,
+        };
+        syntax.check_pattern(bytes, pattern_enc)?;
+
+        // Oniguruma keeps a pointer to the syntax in the compiled regex,
+        // so the regex owns a copy of it at a stable address.
+        let syntax = Box::new(*syntax);
+
+        let mut compile_info = onig_sys::OnigCompileInfo {
+            num_of_elements: 5,
+            pattern_enc: pattern_enc.as_raw(),
+            target_enc: target.as_raw(),
+            syntax: &*syntax as *const Syntax as *mut Syntax as *mut onig_sys::OnigSyntaxType,
+            option: option.bits(),
+            case_fold_flag: case_fold.bits(),
+        };
+
         // We can use this later to get an error message to pass back
         // if regex creation fails.
         let mut error = onig_sys::OnigErrorInfo {
@@ -340,25 +668,61 @@ impl Regex {
         };
 
         let err = unsafe {
-            // Grab a lock to make sure that `onig_new` isn't called by
-            // more than one thread at a time.
+            // Grab a lock to make sure that `onig_new_deluxe` isn't
+            // called by more than one thread at a time.
             let _guard = REGEX_NEW_MUTEX.lock().unwrap();
-            onig_sys::onig_new(
+            warnings::install();
+            onig_sys::onig_new_deluxe(
                 reg_ptr,
                 pattern.start_ptr(),
                 pattern.limit_ptr(),
-                option.bits(),
-                pattern.encoding(),
-                syntax as *const Syntax as *mut Syntax as *mut onig_sys::OnigSyntaxType,
+                &mut compile_info,
                 &mut error,
             )
         };
 
-        if err == onig_sys::ONIG_NORMAL as i32 {
-            Ok(Regex { raw: reg })
+        let result = if err == onig_sys::ONIG_NORMAL as i32 {
+            let mut regex = Regex {
+                raw: reg,
+                pattern: bytes.to_vec(),
+                syntax,
+                compile_options: option,
+                compile_case_fold: case_fold,
+                retry_limit_in_match: None,
+                match_stack_limit: None,
+                names: NameTable::default(),
+            };
+            regex.names = NameTable::new(&regex);
+            Ok(regex)
         } else {
-            Err(Error::from_code_and_info(err, &error))
+            Err(Error::from_code_and_info(err, &error, bytes))
+        };
+
+        // A panicking warning handler unwinds from here, once the
+        // compile lock has been released.
+        warnings::resume_panic();
+        result
+    }
+
+    /// The case fold flags used when none are given explicitly.
+    fn default_case_fold() -> CaseFoldFlags {
+        CaseFoldFlags::from_bits_retain(unsafe { onig_sys::onig_get_default_case_fold_flag() })
+    }
+
+    /// Match Param for this Regex
+    ///
+    /// Creates the `MatchParam` used by the matching and searching
+    /// methods which don't take one explicitly. Any match limits set
+    /// with `RegexBuilder` are applied to it.
+    fn match_param(&self) -> MatchParam {
+        let mut match_param = MatchParam::default();
+        if let Some(limit) = self.retry_limit_in_match {
+            match_param.set_retry_limit_in_match(limit);
         }
+        if let Some(limit) = self.match_stack_limit {
+            match_param.set_match_stack_limit(limit);
+        }
+        match_param
     }
 
     /// Match String
@@ -449,7 +813,7 @@ impl Regex {
     where
         T: EncodedChars,
     {
-        let match_param = MatchParam::default();
+        let match_param = self.match_param();
         let result = self.match_with_param(chars, at, options, region, match_param);
 
         match result {
@@ -478,7 +842,7 @@ impl Regex {
     /// * `at` - The byte index in the passed buffer to start matching
     /// * `options` - The regex match options.
     /// * `region` - The region for return group match range info
-    /// * `match_param` - The match parameters
+    /// * `match_param` - The match parameters, owned or borrowed
     ///
     /// # Returns
     ///
@@ -498,42 +862,52 @@ impl Regex {
     /// assert!(res.is_ok()); // matching did not error
     /// assert!(res.unwrap() == Some(5)); // 5 characters matched
     /// ```
-    pub fn match_with_param<T>(
+    pub fn match_with_param<T, P>(
         &self,
         chars: T,
         at: usize,
         options: SearchOptions,
         region: Option<&mut Region>,
-        match_param: MatchParam,
+        match_param: P,
     ) -> Result<Option<usize>, Error>
     where
         T: EncodedChars,
+        P: Borrow<MatchParam>,
     {
+        let match_param = match_param.borrow();
+        options.check(region.is_some())?;
         if chars.encoding() != self.encoding() {
-            return Err(Error::custom(format!(
-                "Regex encoding does not match haystack encoding ({0:?}, {1:?})",
-                chars.encoding(),
-                self.encoding()
-            )));
+            return Err(Error::custom(
+                ErrorKind::EncodingMismatch,
+                format!(
+                    "Regex encoding does not match haystack encoding ({0:?}, {1:?})",
+                    chars.encoding(),
+                    self.encoding()
+                ),
+            ));
         }
-        let r = unsafe {
-            let offset = chars.start_ptr().add(at);
-            if offset > chars.limit_ptr() {
-                return Err(Error::custom(format!("Offset {} is too large", at)));
-            }
+        let offset = unsafe { chars.start_ptr().add(at) };
+        if offset > chars.limit_ptr() {
+            return Err(Error::custom(
+                ErrorKind::InvalidArgument,
+                format!("Offset {} is too large", at),
+            ));
+        }
+        let region = match region {
+            Some(region) => region as *mut Region as *mut onig_sys::OnigRegion,
+            None => std::ptr::null_mut(),
+        };
+        let r = match_param.run(|| unsafe {
             onig_sys::onig_match_with_param(
                 self.raw,
                 chars.start_ptr(),
                 chars.limit_ptr(),
                 offset,
-                match region {
-                    Some(region) => region as *mut Region as *mut onig_sys::OnigRegion,
-                    None => std::ptr::null_mut(),
-                },
+                region,
                 options.bits(),
                 match_param.as_raw(),
             )
-        };
+        })?;
 
         if r >= 0 {
             Ok(Some(r as usize))
@@ -639,7 +1013,7 @@ impl Regex {
     where
         T: EncodedChars,
     {
-        let match_param = MatchParam::default();
+        let match_param = self.match_param();
         let result = self.search_with_param(chars, from, to, options, region, match_param);
 
         match result {
@@ -669,7 +1043,7 @@ impl Regex {
     ///  * `to` - The byte index in the passed slice to finish search
     ///  * `options` - The options for the search.
     ///  * `region` - The region for return group match range info
-    ///  * `match_param` - The match parameters
+    ///  * `match_param` - The match parameters, owned or borrowed
     ///
     /// # Returns
     ///
@@ -690,49 +1064,77 @@ impl Regex {
     /// assert!(res.is_ok()); // matching did not error
     /// assert!(res.unwrap() == Some(2)); // match starts at character 3
     /// ```
-    pub fn search_with_param<T>(
+    pub fn search_with_param<T, P>(
         &self,
         chars: T,
         from: usize,
         to: usize,
         options: SearchOptions,
         region: Option<&mut Region>,
-        match_param: MatchParam,
+        match_param: P,
     ) -> Result<Option<usize>, Error>
     where
         T: EncodedChars,
+        P: Borrow<MatchParam>,
     {
+        self.search_with_match_param(chars, from, to, options, region, match_param.borrow())
+    }
+
+    /// Search with a borrowed `MatchParam`, so that one can be reused
+    /// between searches.
+    pub(crate) fn search_with_match_param<T>(
+        &self,
+        chars: T,
+        from: usize,
+        to: usize,
+        options: SearchOptions,
+        region: Option<&mut Region>,
+        match_param: &MatchParam,
+    ) -> Result<Option<usize>, Error>
+    where
+        T: EncodedChars,
+    {
+        options.check(region.is_some())?;
         let (beg, end) = (chars.start_ptr(), chars.limit_ptr());
         if chars.encoding() != self.encoding() {
-            return Err(Error::custom(format!(
-                "Regex encoding does not match haystack encoding ({0:?}, {1:?})",
-                chars.encoding(),
-                self.encoding()
-            )));
+            return Err(Error::custom(
+                ErrorKind::EncodingMismatch,
+                format!(
+                    "Regex encoding does not match haystack encoding ({0:?}, {1:?})",
+                    chars.encoding(),
+                    self.encoding()
+                ),
+            ));
         }
-        let r = unsafe {
-            let start = beg.add(from);
-            let range = beg.add(to);
-            if start > end {
-                return Err(Error::custom("Start of match should be before end"));
-            }
-            if range > end {
-                return Err(Error::custom("Limit of match should be before end"));
-            }
+        let (start, range) = unsafe { (beg.add(from), beg.add(to)) };
+        if start > end {
+            return Err(Error::custom(
+                ErrorKind::InvalidArgument,
+                "Start of match should be before end",
+            ));
+        }
+        if range > end {
+            return Err(Error::custom(
+                ErrorKind::InvalidArgument,
+                "Limit of match should be before end",
+            ));
+        }
+        let region = match region {
+            Some(region) => region as *mut Region as *mut onig_sys::OnigRegion,
+            None => std::ptr::null_mut(),
+        };
+        let r = match_param.run(|| unsafe {
             onig_sys::onig_search_with_param(
                 self.raw,
                 beg,
                 end,
                 start,
                 range,
-                match region {
-                    Some(region) => region as *mut Region as *mut onig_sys::OnigRegion,
-                    None => std::ptr::null_mut(),
-                },
+                region,
                 options.bits(),
                 match_param.as_raw(),
             )
-        };
+        })?;
 
         if r >= 0 {
             Ok(Some(r as usize))
@@ -817,10 +1219,24 @@ impl Regex {
     ///
     /// # Returns
     ///
-    /// Returns a reference to an oniguruma encoding which was used
-    /// when this regex was created.
-    pub fn encoding(&self) -> onig_sys::OnigEncoding {
-        unsafe { onig_sys::onig_get_encoding(self.raw) }
+    /// Returns the encoding which was used when this regex was
+    /// created.
+    pub fn encoding(&self) -> Encoding {
+        let raw = unsafe { onig_sys::onig_get_encoding(self.raw) };
+        // Regexes can only be compiled with an `Encoding`.
+        Encoding::from_raw(raw).unwrap()
+    }
+
+    /// Get the Case Folding Flags of the Regex
+    ///
+    /// # Returns
+    ///
+    /// Returns the case folding flags this regex was compiled with.
+    /// These control how `REGEX_OPTION_IGNORECASE` compares
+    /// characters, and can be set per regex with
+    /// `RegexBuilder::case_fold`.
+    pub fn case_fold_flag(&self) -> CaseFoldFlags {
+        CaseFoldFlags::from_bits_retain(unsafe { onig_sys::onig_get_case_fold_flag(self.raw) })
     }
 
     /// Get the Number of Capture Groups in this Pattern
@@ -832,6 +1248,155 @@ impl Regex {
     pub fn capture_histories_len(&self) -> usize {
         unsafe { onig_sys::onig_number_of_capture_histories(self.raw) as usize }
     }
+
+    /// Get the Pattern Source
+    ///
+    /// # Returns
+    ///
+    /// Returns the pattern this regex was compiled from, or `None` if
+    /// the pattern isn't UTF-8 text. This can only happen for regexes
+    /// compiled from a pattern in another encoding, in which case
+    /// `as_bytes` returns the pattern instead.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use onig::Regex;
+    /// let regex = Regex::new(r"h(\w+)o").unwrap();
+    /// assert_eq!(regex.as_str(), Some(r"h(\w+)o"));
+    /// ```
+    pub fn as_str(&self) -> Option<&str> {
+        let encoding = self.encoding();
+        if encoding == Encoding::Utf8 || (encoding.is_ascii_compatible() && self.pattern.is_ascii())
+        {
+            str::from_utf8(&self.pattern).ok()
+        } else {
+            None
+        }
+    }
+
+    /// Get the Pattern Bytes
+    ///
+    /// # Returns
+    ///
+    /// Returns the pattern this regex was compiled from, in the
+    /// regex's encoding.
+    pub fn as_bytes(&self) -> &[u8] {
+        &self.pattern
+    }
+
+    /// Get the Options of the Regex
+    ///
+    /// # Returns
+    ///
+    /// Returns the options this regex was compiled with, including any
+    /// set by its syntax.
+    pub fn options(&self) -> RegexOptions {
+        RegexOptions::from_bits_retain(unsafe { onig_sys::onig_get_options(self.raw) })
+    }
+
+    /// Get the Syntax of the Regex
+    ///
+    /// # Returns
+    ///
+    /// Returns the syntax this regex was compiled with. The regex
+    /// keeps its own copy, so the `Syntax` it was compiled with can be
+    /// changed or dropped afterwards.
+    pub fn syntax(&self) -> &Syntax {
+        &self.syntax
+    }
+
+    /// Are Unnamed Groups Captured?
+    ///
+    /// # Returns
+    ///
+    /// Returns false if plain `(...)` groups in this regex don't
+    /// capture. This is the case when the pattern also contains named
+    /// groups, unless `REGEX_OPTION_CAPTURE_GROUP` is set.
+    pub fn noname_group_capture_is_active(&self) -> bool {
+        unsafe { onig_sys::onig_noname_group_capture_is_active(self.raw) != 0 }
+    }
+}
+
+impl Clone for Regex {
+    /// Compile the pattern again with the options, syntax and case
+    /// fold flags this regex was compiled with.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the pattern no longer compiles. This can only happen
+    /// if the process-wide `Limits` have been lowered since the regex
+    /// was compiled, or Oniguruma runs out of memory.
+    fn clone(&self) -> Self {
+        let encoding = self.encoding();
+        let pattern = EncodedBytes::from_parts(&self.pattern, encoding);
+        let mut regex = Regex::compile(
+            pattern,
+            encoding,
+            self.compile_options,
+            &self.syntax,
+            self.compile_case_fold,
+        )
+        .expect("Onig: Regex compile error: recompiling a valid pattern failed");
+        regex.retry_limit_in_match = self.retry_limit_in_match;
+        regex.match_stack_limit = self.match_stack_limit;
+        regex
+    }
+}
+
+impl PartialEq for Regex {
+    fn eq(&self, other: &Regex) -> bool {
+        self.pattern == other.pattern
+            && self.options() == other.options()
+            && self.syntax == other.syntax
+            && self.encoding() == other.encoding()
+            && self.case_fold_flag() == other.case_fold_flag()
+    }
+}
+
+impl Eq for Regex {}
+
+impl Hash for Regex {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.pattern.hash(state);
+        self.options().hash(state);
+        self.syntax.hash(state);
+        self.encoding().hash(state);
+        self.case_fold_flag().hash(state);
+    }
+}
+
+impl fmt::Debug for Regex {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self.as_str() {
+            Some(pattern) => f.debug_tuple("Regex").field(&pattern).finish(),
+            None => f.debug_tuple("Regex").field(&self.pattern).finish(),
+        }
+    }
+}
+
+impl str::FromStr for Regex {
+    type Err = Error;
+
+    fn from_str(pattern: &str) -> Result<Regex, Error> {
+        Regex::new(pattern)
+    }
+}
+
+impl TryFrom<&str> for Regex {
+    type Error = Error;
+
+    fn try_from(pattern: &str) -> Result<Regex, Error> {
+        Regex::new(pattern)
+    }
+}
+
+impl TryFrom<String> for Regex {
+    type Error = Error;
+
+    fn try_from(pattern: String) -> Result<Regex, Error> {
+        Regex::new(&pattern)
+    }
 }
 
 impl Drop for Regex {
@@ -859,6 +1424,153 @@ mod tests {
         let e = Regex::new("\\p{foo}").unwrap_err();
         assert_eq!(e.code(), -223);
         assert_eq!(e.description(), "invalid character property name {foo}");
+        assert_eq!(e.kind(), ErrorKind::Syntax);
+    }
+
+    #[test]
+    fn test_regex_invalid_span() {
+        let e = Regex::new("ab\\p{foo}").unwrap_err();
+        assert_eq!(e.pattern(), Some(&b"ab\\p{foo}"[..]));
+        assert_eq!(e.span(), Some(5..8));
+        assert_eq!(e.diagnostic().unwrap(), "ab\\p{foo}\n     ^^^");
+
+        let e = Regex::new("(?x) a\n\t\\p{é}\n").unwrap_err();
+        assert_eq!(e.span(), Some(11..13));
+        assert_eq!(e.diagnostic().unwrap(), "\t\\p{é}\n\t   ^");
+
+        let e = Regex::new("a)").unwrap_err();
+        assert_eq!(e.pattern(), Some(&b"a)"[..]));
+        assert_eq!(e.span(), None);
+        assert_eq!(e.diagnostic(), None);
+        assert_eq!(Error::from_code(onig_sys::ONIGERR_MEMORY).pattern(), None);
+    }
+
+    #[test]
+    fn test_regex_options_is_ascii() {
+        let regex = Regex::with_options(
+            r"\w+",
+            RegexOptions::REGEX_OPTION_WORD_IS_ASCII,
+            Syntax::default(),
+        )
+        .unwrap();
+        assert_eq!(regex.find("été ok"), Some((2, 3)));
+        assert_eq!(Regex::new(r"\w+").unwrap().find("été ok"), Some((0, 5)));
+    }
+
+    #[test]
+    fn test_invalid_regex_options() {
+        let e = Regex::with_options(
+            "a",
+            RegexOptions::REGEX_OPTION_TEXT_SEGMENT_WORD
+                | RegexOptions::REGEX_OPTION_TEXT_SEGMENT_EXTENDED_GRAPHEME_CLUSTER,
+            Syntax::default(),
+        )
+        .unwrap_err();
+        assert_eq!(e.kind(), ErrorKind::InvalidArgument);
+
+        let e = Regex::with_options(
+            "a",
+            RegexOptions::REGEX_OPTION_CAPTURE_GROUP
+                | RegexOptions::REGEX_OPTION_DONT_CAPTURE_GROUP,
+            Syntax::default(),
+        )
+        .unwrap_err();
+        assert_eq!(e.code(), onig_sys::ONIGERR_INVALID_COMBINATION_OF_OPTIONS);
+        assert_eq!(e.kind(), ErrorKind::InvalidArgument);
+
+        // NEGATE_SINGLELINE cancels SINGLELINE, as syntaxes like Perl's
+        // set it by default.
+        let regex = Regex::with_options(
+            "a$",
+            Syntax::perl().options() | RegexOptions::REGEX_OPTION_NEGATE_SINGLELINE,
+            Syntax::perl(),
+        )
+        .unwrap();
+        assert_eq!(regex.find("a\nb"), Some((0, 1)));
+
+        let search_only = RegexOptions::from_bits_retain(onig_sys::ONIG_OPTION_NOTBOL);
+        let e = Regex::with_options("a", search_only, Syntax::default()).unwrap_err();
+        assert_eq!(e.kind(), ErrorKind::InvalidArgument);
+    }
+
+    #[test]
+    fn test_search_not_begin_string() {
+        let regex = Regex::new(r"\Aa").unwrap();
+        let find = |options| {
+            regex
+                .search_with_param("ab", 0, 2, options, None, MatchParam::default())
+                .unwrap()
+        };
+        assert_eq!(find(SearchOptions::SEARCH_OPTION_NONE), Some(0));
+        assert_eq!(find(SearchOptions::SEARCH_OPTION_NOT_BEGIN_STRING), None);
+    }
+
+    #[test]
+    fn test_invalid_search_options() {
+        let regex = Regex::new("a").unwrap();
+        let compile_only = SearchOptions::from_bits_retain(onig_sys::ONIG_OPTION_IGNORECASE);
+        let e = regex
+            .search_with_param("a", 0, 1, compile_only, None, MatchParam::default())
+            .unwrap_err();
+        assert_eq!(e.kind(), ErrorKind::InvalidArgument);
+
+        let mut region = Region::new();
+        let e = regex
+            .match_with_param(
+                "a",
+                0,
+                SearchOptions::SEARCH_OPTION_POSIX_REGION,
+                Some(&mut region),
+                MatchParam::default(),
+            )
+            .unwrap_err();
+        assert_eq!(e.kind(), ErrorKind::InvalidArgument);
+    }
+
+    #[test]
+    fn test_error_kind() {
+        assert_eq!(
+            Error::from_code(onig_sys::ONIGERR_MATCH_STACK_LIMIT_OVER).kind(),
+            ErrorKind::MatchStackLimitExceeded
+        );
+        assert_eq!(
+            Error::from_code(onig_sys::ONIGERR_NOT_SUPPORTED_ENCODING_COMBINATION).kind(),
+            ErrorKind::EncodingMismatch
+        );
+        assert_eq!(
+            Error::from_code(onig_sys::ONIGERR_PARSER_BUG).kind(),
+            ErrorKind::Internal
+        );
+        assert!(
+            Error::from_code(onig_sys::ONIGERR_RETRY_LIMIT_IN_MATCH_OVER)
+                .kind()
+                .is_limit_exceeded()
+        );
+        assert!(!ErrorKind::Syntax.is_limit_exceeded());
+
+        let regex = Regex::new("foo").unwrap();
+        let e = regex
+            .match_with_param(
+                "foo",
+                4,
+                SearchOptions::SEARCH_OPTION_NONE,
+                None,
+                MatchParam::default(),
+            )
+            .unwrap_err();
+        assert_eq!(e.kind(), ErrorKind::InvalidArgument);
+
+        let e = regex
+            .search_with_param(
+                EncodedBytes::ascii(b"foo"),
+                0,
+                3,
+                SearchOptions::SEARCH_OPTION_NONE,
+                None,
+                MatchParam::default(),
+            )
+            .unwrap_err();
+        assert_eq!(e.kind(), ErrorKind::EncodingMismatch);
     }
 
     #[test]
@@ -937,6 +1649,117 @@ mod tests {
         assert_eq!(regex.find("hey, honey!"), None);
     }
 
+    #[test]
+    fn test_regex_find_code_units() {
+        let pattern: Vec<u16> = "é(l+)".encode_utf16().collect();
+        let regex = Regex::with_encoding(EncodedUnits::new(&pattern)).unwrap();
+        let text: Vec<u16> = "hé😀 héllo".encode_utf16().collect();
+        let text = EncodedUnits::new(&text);
+        let mut region = Region::new();
+        regex
+            .search_with_encoding(
+                text,
+                0,
+                text.len(),
+                SearchOptions::SEARCH_OPTION_NONE,
+                Some(&mut region),
+            )
+            .unwrap();
+        assert_eq!(text.unit_pos(region.pos(0).unwrap()), (6, 9));
+        assert_eq!(text.unit_pos(region.pos(1).unwrap()), (7, 9));
+
+        let pattern: Vec<char> = "😀.".chars().collect();
+        let regex = Regex::with_encoding(EncodedUnits::new(&pattern)).unwrap();
+        let text: Vec<char> = "hé😀 x".chars().collect();
+        let text = EncodedUnits::new(&text);
+        let pos = regex.find_with_encoding(text).unwrap();
+        assert_eq!(text.unit_pos(pos), (2, 4));
+    }
+
+    #[test]
+    fn test_regex_introspection() {
+        let regex = Regex::with_options(
+            "a(?<b>.)",
+            RegexOptions::REGEX_OPTION_IGNORECASE,
+            Syntax::perl_ng(),
+        )
+        .unwrap();
+        assert_eq!(regex.as_str(), Some("a(?<b>.)"));
+        assert_eq!(regex.as_bytes(), b"a(?<b>.)");
+        assert!(regex
+            .options()
+            .contains(RegexOptions::REGEX_OPTION_IGNORECASE));
+        assert!(regex.options().contains(Syntax::perl_ng().options()));
+        assert_eq!(regex.syntax(), Syntax::perl_ng());
+        assert!(!regex.noname_group_capture_is_active());
+        assert!(Regex::new("a(.)").unwrap().noname_group_capture_is_active());
+        assert_eq!(format!("{:?}", regex), r#"Regex("a(?<b>.)")"#);
+
+        let pattern: Vec<u16> = "a".encode_utf16().collect();
+        let regex = Regex::with_encoding(EncodedUnits::new(&pattern)).unwrap();
+        assert_eq!(regex.as_str(), None);
+        assert_eq!(regex.as_bytes(), b"a\0");
+    }
+
+    #[test]
+    fn test_regex_owns_syntax() {
+        let regex = {
+            let mut syntax = *Syntax::ruby();
+            syntax.enable_operators(SyntaxOperator::SYNTAX_OPERATOR_VARIABLE_META_CHARACTERS);
+            syntax.set_meta_char(MetaCharType::META_CHAR_ANYCHAR, MetaChar::Character('_'));
+            Regex::with_options("a_c", RegexOptions::REGEX_OPTION_NONE, &syntax).unwrap()
+        };
+        let raw_syntax = unsafe { onig_sys::onig_get_syntax(regex.raw) };
+        assert_eq!(raw_syntax as *const Syntax, regex.syntax() as *const Syntax);
+        assert!(regex.is_match("abc"));
+        assert!(!regex.is_match("a_d"));
+        assert_eq!(regex.clone(), regex);
+    }
+
+    #[test]
+    fn test_regex_clone_and_eq() {
+        use std::collections::HashSet;
+
+        let regex = RegexBuilder::new("a.c")
+            .options(RegexOptions::REGEX_OPTION_NEGATE_SINGLELINE)
+            .syntax(Syntax::perl())
+            .retry_limit_in_match(100)
+            .build()
+            .unwrap();
+        let clone = regex.clone();
+        assert_eq!(regex, clone);
+        assert_eq!(clone.options(), regex.options());
+        assert_eq!(clone.retry_limit_in_match, Some(100));
+        assert!(clone.is_match("abc"));
+
+        let regex = Regex::with_options(
+            "a$",
+            Syntax::perl().options() | RegexOptions::REGEX_OPTION_NEGATE_SINGLELINE,
+            Syntax::perl(),
+        )
+        .unwrap();
+        assert_eq!(regex.clone(), regex);
+
+        let latin1 = RegexBuilder::new("caf.")
+            .target_encoding(Encoding::Iso8859_1)
+            .build()
+            .unwrap();
+        assert_eq!(latin1.clone().encoding(), Encoding::Iso8859_1);
+        assert_ne!(latin1, Regex::new("caf.").unwrap());
+
+        let mut set = HashSet::new();
+        set.insert(Regex::new("a+").unwrap());
+        set.insert("a+".parse::<Regex>().unwrap());
+        set.insert(Regex::try_from("a+").unwrap());
+        set.insert(
+            Regex::with_options("a+", RegexOptions::REGEX_OPTION_NONE, Syntax::perl()).unwrap(),
+        );
+        set.insert(Regex::try_from(String::from("b+")).unwrap());
+        assert_eq!(set.len(), 3);
+
+        assert!("a(".parse::<Regex>().is_err());
+    }
+
     #[test]
     fn test_regex_captures_len() {
         let regex = Regex::new("(he)(l+)(o)").unwrap();
@@ -0,0 +1,158 @@
+//! Engine Limits
+//!
+//! Contains the definition of `Limits`, which reads and sets the
+//! process-wide limits Oniguruma applies when compiling and matching
+//! regexes. These are useful for hardening against hostile patterns.
+
+use std::os::raw::{c_int, c_uint, c_ulong};
+
+use super::{Error, REGEX_NEW_MUTEX};
+
+/// Process-wide Engine Limits
+///
+/// Getters and setters for the global limits used by Oniguruma. The
+/// match and search limits are the defaults for every `MatchParam`
+/// created after they are set, and can be overridden for a single
+/// search. The others apply to every regex compiled, or search run,
+/// afterwards.
+///
+/// A limit of `0` means unlimited for the retry and subexpression
+/// call limits.
+///
+/// # Examples
+///
+/// ```
+/// use onig::Limits;
+///
+/// let depth = Limits::parse_depth_limit();
+/// Limits::set_parse_depth_limit(depth * 2).unwrap();
+/// assert_eq!(Limits::parse_depth_limit(), depth * 2);
+/// # Limits::set_parse_depth_limit(depth).unwrap();
+/// ```
+#[derive(Debug, Clone, Copy)]
+pub struct Limits;
+
+impl Limits {
+    /// Get the default match stack limit. `0` means unlimited.
+    pub fn match_stack_limit_size() -> u32 {
+        unsafe { onig_sys::onig_get_match_stack_limit_size() }
+    }
+
+    /// Set the default match stack limit. `0` means unlimited.
+    pub fn set_match_stack_limit_size(size: u32) -> Result<(), Error> {
+        set(|| unsafe { onig_sys::onig_set_match_stack_limit_size(size as c_uint) })
+    }
+
+    /// Get the default limit on retries within a single match attempt.
+    pub fn retry_limit_in_match() -> u32 {
+        saturate(unsafe { onig_sys::onig_get_retry_limit_in_match() })
+    }
+
+    /// Set the default limit on retries within a single match attempt.
+    pub fn set_retry_limit_in_match(limit: u32) -> Result<(), Error> {
+        set(|| unsafe { onig_sys::onig_set_retry_limit_in_match(c_ulong::from(limit)) })
+    }
+
+    /// Get the default limit on retries across a whole search.
+    pub fn retry_limit_in_search() -> u32 {
+        saturate(unsafe { onig_sys::onig_get_retry_limit_in_search() })
+    }
+
+    /// Set the default limit on retries across a whole search.
+    pub fn set_retry_limit_in_search(limit: u32) -> Result<(), Error> {
+        set(|| unsafe { onig_sys::onig_set_retry_limit_in_search(c_ulong::from(limit)) })
+    }
+
+    /// Get the maximum nesting depth of a pattern when it is parsed.
+    pub fn parse_depth_limit() -> u32 {
+        unsafe { onig_sys::onig_get_parse_depth_limit() }
+    }
+
+    /// Set the maximum nesting depth of a pattern when it is parsed.
+    /// `0` restores Oniguruma's default.
+    pub fn set_parse_depth_limit(depth: u32) -> Result<(), Error> {
+        set(|| unsafe { onig_sys::onig_set_parse_depth_limit(depth as c_uint) })
+    }
+
+    /// Set the maximum number of capture groups in a pattern.
+    /// Oniguruma has no getter for this limit.
+    pub fn set_capture_num_limit(num: i32) -> Result<(), Error> {
+        set(|| unsafe { onig_sys::onig_set_capture_num_limit(num as c_int) })
+    }
+
+    /// Get the limit on subexpression calls, such as `\g<name>`, in a
+    /// single search.
+    pub fn subexp_call_limit_in_search() -> u32 {
+        saturate(unsafe { onig_sys::onig_get_subexp_call_limit_in_search() })
+    }
+
+    /// Set the limit on subexpression calls, such as `\g<name>`, in a
+    /// single search.
+    pub fn set_subexp_call_limit_in_search(limit: u32) -> Result<(), Error> {
+        set(|| unsafe { onig_sys::onig_set_subexp_call_limit_in_search(c_ulong::from(limit)) })
+    }
+
+    /// Get the maximum nesting level of subexpression calls.
+    pub fn subexp_call_max_nest_level() -> i32 {
+        unsafe { onig_sys::onig_get_subexp_call_max_nest_level() }
+    }
+
+    /// Set the maximum nesting level of subexpression calls.
+    pub fn set_subexp_call_max_nest_level(level: i32) -> Result<(), Error> {
+        set(|| unsafe { onig_sys::onig_set_subexp_call_max_nest_level(level as c_int) })
+    }
+}
+
+/// Update a global limit, holding the compile lock so that no regex
+/// is compiled while it changes.
+fn set<F>(update: F) -> Result<(), Error>
+where
+    F: FnOnce() -> c_int,
+{
+    let _guard = REGEX_NEW_MUTEX.lock().unwrap();
+    check(update())
+}
+
+/// Convert the result of a setter.
+fn check(r: c_int) -> Result<(), Error> {
+    if r == onig_sys::ONIG_NORMAL as c_int {
+        Ok(())
+    } else {
+        Err(Error::from_code(r))
+    }
+}
+
+/// Convert a `c_ulong` limit, saturating on platforms where it is
+/// larger than `u32`.
+fn saturate(limit: c_ulong) -> u32 {
+    u32::try_from(limit).unwrap_or(u32::MAX)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_limits_defaults() {
+        assert!(Limits::parse_depth_limit() > 0);
+        assert!(Limits::retry_limit_in_match() > 0);
+        assert!(Limits::subexp_call_max_nest_level() > 0);
+    }
+
+    #[test]
+    fn test_limits_set_get() {
+        let level = Limits::subexp_call_max_nest_level();
+        Limits::set_subexp_call_max_nest_level(level + 1).unwrap();
+        assert_eq!(Limits::subexp_call_max_nest_level(), level + 1);
+        Limits::set_subexp_call_max_nest_level(level).unwrap();
+
+        let limit = Limits::subexp_call_limit_in_search();
+        Limits::set_subexp_call_limit_in_search(limit).unwrap();
+        assert_eq!(Limits::subexp_call_limit_in_search(), limit);
+    }
+
+    #[test]
+    fn test_limits_invalid() {
+        assert!(Limits::set_capture_num_limit(-1).is_err());
+    }
+}
@@ -3,11 +3,73 @@
 //! Contains the definition for the `MatchParam` struct. This can be
 //! used to control the behavior of searching and matching.
 
-use std::os::raw::{c_uint, c_ulong};
+use std::any::Any;
+use std::os::raw::{c_int, c_uint, c_ulong, c_void};
+use std::panic::{self, AssertUnwindSafe};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::time::Instant;
+
+use super::{CalloutArgs, CalloutResult, Error};
+
+/// The retry budget of the first attempt at a search which has a
+/// deadline or cancellation flag. Each further attempt doubles it.
+const FIRST_INTERRUPT_CHECK: c_ulong = 1 << 16;
+
+/// A boxed callout handler.
+type CalloutHandler = Box<dyn FnMut(&CalloutArgs) -> CalloutResult + Send>;
+
+/// The callout handlers of a `MatchParam`, passed to Oniguruma as the
+/// callout user data.
+#[derive(Default)]
+struct Callouts {
+    progress: Option<CalloutHandler>,
+    retraction: Option<CalloutHandler>,
+    panic: Option<Box<dyn Any + Send>>,
+    deadline: Option<Instant>,
+    cancel: Option<Arc<AtomicBool>>,
+    interrupted: Option<Interrupt>,
+}
+
+/// Why a search was stopped early.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Interrupt {
+    Deadline,
+    Cancelled,
+}
+
+impl Callouts {
+    /// Check the deadline and cancellation flag.
+    fn check_interrupt(&self) -> Option<Interrupt> {
+        if self
+            .cancel
+            .as_ref()
+            .is_some_and(|c| c.load(Ordering::Relaxed))
+        {
+            Some(Interrupt::Cancelled)
+        } else if self.deadline.is_some_and(|d| Instant::now() >= d) {
+            Some(Interrupt::Deadline)
+        } else {
+            None
+        }
+    }
+}
+
+impl From<Interrupt> for Error {
+    fn from(interrupt: Interrupt) -> Error {
+        match interrupt {
+            Interrupt::Deadline => Error::deadline_exceeded(),
+            Interrupt::Cancelled => Error::cancelled(),
+        }
+    }
+}
 
 /// Parameters for a Match or Search.
+#[derive(Debug)]
 pub struct MatchParam {
     raw: *mut onig_sys::OnigMatchParam,
+    callouts: *mut Callouts,
+    retry_limit_in_search: c_ulong,
 }
 
 impl MatchParam {
@@ -25,20 +87,260 @@ impl MatchParam {
         }
     }
 
+    /// Set the retry limit in search
+    ///
+    /// Limits the total number of retries across every match attempt
+    /// in a search. `0` means unlimited.
+    pub fn set_retry_limit_in_search(&mut self, limit: u32) {
+        self.retry_limit_in_search = c_ulong::from(limit);
+        unsafe {
+            onig_sys::onig_set_retry_limit_in_search_of_match_param(
+                self.raw,
+                self.retry_limit_in_search,
+            );
+        }
+    }
+
+    /// Set the progress callout handler
+    ///
+    /// The handler is called for each contents callout `(?{...})` in
+    /// the pattern as the match progresses through it, and decides
+    /// whether the match continues.
+    ///
+    /// If the handler panics the search is aborted and the panic is
+    /// resumed once the search returns.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use onig::{CalloutResult, MatchParam, Regex, SearchOptions};
+    ///
+    /// let r = Regex::new(r"\d+(?{even})").unwrap();
+    /// let mut mp = MatchParam::default();
+    /// mp.on_progress(|args| {
+    ///     if args.position() % 2 == 0 {
+    ///         CalloutResult::Success
+    ///     } else {
+    ///         CalloutResult::Fail
+    ///     }
+    /// });
+    /// let res = r.search_with_param("12345", 0, 5, SearchOptions::SEARCH_OPTION_NONE, None, mp);
+    /// assert_eq!(res.unwrap(), Some(0));
+    /// ```
+    pub fn on_progress<F>(&mut self, handler: F)
+    where
+        F: FnMut(&CalloutArgs) -> CalloutResult + Send + 'static,
+    {
+        self.callouts().progress = Some(Box::new(handler));
+        unsafe {
+            onig_sys::onig_set_progress_callout_of_match_param(self.raw, Some(progress_callout));
+        }
+    }
+
+    /// Set the retraction callout handler
+    ///
+    /// The handler is called for each contents callout which is marked
+    /// to run on retraction, such as `(?{...}<)` or `(?{...}X)`, when
+    /// the match backtracks past it.
+    pub fn on_retraction<F>(&mut self, handler: F)
+    where
+        F: FnMut(&CalloutArgs) -> CalloutResult + Send + 'static,
+    {
+        self.callouts().retraction = Some(Box::new(handler));
+        unsafe {
+            onig_sys::onig_set_retraction_callout_of_match_param(
+                self.raw,
+                Some(retraction_callout),
+            );
+        }
+    }
+
+    /// Set a deadline for the search
+    ///
+    /// A search or match using this parameter stops with an error for
+    /// which `Error::is_deadline_exceeded` is true once the deadline
+    /// has passed.
+    ///
+    /// The deadline is checked at each callout, and periodically for
+    /// all patterns by restarting the search with a growing retry
+    /// budget. Each restart is sized from the time left, so a search
+    /// shouldn't run much past the deadline. Callout handlers may be
+    /// invoked more than once for the same match attempt as a result.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use std::time::{Duration, Instant};
+    /// use onig::{MatchParam, Regex, SearchOptions};
+    ///
+    /// let r = Regex::new("(x+x+)+y").unwrap();
+    /// let text = "x".repeat(30);
+    /// let mut mp = MatchParam::default();
+    /// mp.set_retry_limit_in_match(u32::MAX);
+    /// mp.set_deadline(Instant::now() + Duration::from_millis(10));
+    /// let res = r.search_with_param(text.as_str(), 0, text.len(),
+    ///                               SearchOptions::SEARCH_OPTION_NONE, None, mp);
+    /// assert!(res.unwrap_err().is_deadline_exceeded());
+    /// ```
+    pub fn set_deadline(&mut self, deadline: Instant) {
+        self.callouts().deadline = Some(deadline);
+    }
+
+    /// Set a cancellation flag for the search
+    ///
+    /// A search or match using this parameter stops with an error for
+    /// which `Error::is_cancelled` is true once `flag` is set to true,
+    /// e.g. from another thread. The flag is checked in the same way as
+    /// the deadline given to `set_deadline`.
+    pub fn set_cancel_flag(&mut self, flag: Arc<AtomicBool>) {
+        self.callouts().cancel = Some(flag);
+    }
+
+    /// Get the callout handlers.
+    fn callouts(&mut self) -> &mut Callouts {
+        unsafe { &mut *self.callouts }
+    }
+
+    /// Run a search or match using this parameter
+    ///
+    /// `search` calls into Oniguruma with this parameter and returns
+    /// the raw result. If a deadline or cancellation flag is set the
+    /// search is run with a limited retry budget, and restarted with a
+    /// larger one until it completes or is interrupted. Any panic from
+    /// a callout handler is resumed once the search returns.
+    pub(crate) fn run<F>(&self, mut search: F) -> Result<c_int, Error>
+    where
+        F: FnMut() -> c_int,
+    {
+        let callouts = self.callouts;
+        let interruptible =
+            unsafe { (*callouts).deadline.is_some() || (*callouts).cancel.is_some() };
+        if !interruptible {
+            let r = search();
+            self.resume_callout_panic();
+            return Ok(r);
+        }
+
+        let limit = self.retry_limit_in_search;
+        let mut budget = FIRST_INTERRUPT_CHECK;
+        let result = loop {
+            if let Some(interrupt) = unsafe { (*callouts).check_interrupt() } {
+                break Err(interrupt.into());
+            }
+            let capped = limit != 0 && budget >= limit;
+            unsafe {
+                let budget = if capped { limit } else { budget };
+                onig_sys::onig_set_retry_limit_in_search_of_match_param(self.raw, budget);
+            }
+            let started = Instant::now();
+            let r = search();
+            if let Some(interrupt) = unsafe { (*callouts).interrupted.take() } {
+                break Err(interrupt.into());
+            }
+            if capped || r != onig_sys::ONIGERR_RETRY_LIMIT_IN_SEARCH_OVER {
+                break Ok(r);
+            }
+
+            // Restarting repeats the work done so far, so only grow the
+            // budget as far as the time left before the deadline allows.
+            let mut next = budget.saturating_mul(2);
+            if let Some(deadline) = unsafe { (*callouts).deadline } {
+                let elapsed = started.elapsed().as_nanos().max(1);
+                let remaining = deadline
+                    .saturating_duration_since(Instant::now())
+                    .as_nanos();
+                let affordable = remaining.saturating_mul(u128::from(budget)) / elapsed;
+                if affordable <= u128::from(budget) {
+                    break Err(Interrupt::Deadline.into());
+                }
+                next = next.min(affordable.min(c_ulong::MAX as u128) as c_ulong);
+            }
+            budget = next;
+        };
+        unsafe {
+            onig_sys::onig_set_retry_limit_in_search_of_match_param(self.raw, limit);
+        }
+        self.resume_callout_panic();
+        result
+    }
+
+    /// Resume a panic raised by a callout handler during the last
+    /// search or match with this parameter.
+    fn resume_callout_panic(&self) {
+        if let Some(payload) = unsafe { (*self.callouts).panic.take() } {
+            panic::resume_unwind(payload);
+        }
+    }
+
     /// Get the Raw `OnigMatchParam` Pointer
     pub fn as_raw(&self) -> *mut onig_sys::OnigMatchParam {
         self.raw
     }
 }
 
+unsafe extern "C" fn progress_callout(
+    args: *mut onig_sys::OnigCalloutArgs,
+    user_data: *mut c_void,
+) -> c_int {
+    let callouts = user_data as *mut Callouts;
+    catch_callout_panic(user_data, || match (*callouts).progress {
+        Some(ref mut handler) => handler(&CalloutArgs::from_raw(args)),
+        None => CalloutResult::Success,
+    })
+}
+
+unsafe extern "C" fn retraction_callout(
+    args: *mut onig_sys::OnigCalloutArgs,
+    user_data: *mut c_void,
+) -> c_int {
+    let callouts = user_data as *mut Callouts;
+    catch_callout_panic(user_data, || match (*callouts).retraction {
+        Some(ref mut handler) => handler(&CalloutArgs::from_raw(args)),
+        None => CalloutResult::Success,
+    })
+}
+
+/// Run a callout handler, catching any panic so that it doesn't unwind
+/// through Oniguruma. The panic is stored in the callout user data of
+/// the `MatchParam` and the search is aborted, so that the panic can be
+/// resumed once the search returns.
+pub(crate) unsafe fn catch_callout_panic<F>(user_data: *mut c_void, handler: F) -> c_int
+where
+    F: FnOnce() -> CalloutResult,
+{
+    let callouts = user_data as *mut Callouts;
+    if (*callouts).panic.is_some() {
+        return onig_sys::ONIG_ABORT;
+    }
+    if let Some(interrupt) = (*callouts).check_interrupt() {
+        (*callouts).interrupted = Some(interrupt);
+        return onig_sys::ONIG_ABORT;
+    }
+    match panic::catch_unwind(AssertUnwindSafe(handler)) {
+        Ok(result) => result.as_raw(),
+        Err(payload) => {
+            (*callouts).panic = Some(payload);
+            onig_sys::ONIG_ABORT
+        }
+    }
+}
+
+unsafe impl Send for MatchParam {}
+
 impl Default for MatchParam {
     fn default() -> Self {
+        let callouts = Box::into_raw(Box::<Callouts>::default());
         let raw = unsafe {
             let new = onig_sys::onig_new_match_param();
             onig_sys::onig_initialize_match_param(new);
+            onig_sys::onig_set_callout_user_data_of_match_param(new, callouts as *mut c_void);
             new
         };
-        MatchParam { raw }
+        MatchParam {
+            raw,
+            callouts,
+            retry_limit_in_search: unsafe { onig_sys::onig_get_retry_limit_in_search() },
+        }
     }
 }
 
@@ -46,6 +348,7 @@ impl Drop for MatchParam {
     fn drop(&mut self) {
         unsafe {
             onig_sys::onig_free_match_param(self.raw);
+            drop(Box::from_raw(self.callouts));
         }
     }
 }
@@ -53,6 +356,7 @@ impl Drop for MatchParam {
 #[cfg(test)]
 mod test {
 
+    use super::super::{Regex, SearchOptions};
     use super::*;
 
     #[test]
@@ -71,4 +375,87 @@ mod test {
         let mut mp = MatchParam::default();
         mp.set_retry_limit_in_match(1000);
     }
+
+    #[test]
+    pub fn set_retry_limit_in_search() {
+        let mut mp = MatchParam::default();
+        mp.set_retry_limit_in_match(u32::MAX);
+        mp.set_retry_limit_in_search(1000);
+        let e = pathological_search(mp).unwrap_err();
+        assert_eq!(e.code(), onig_sys::ONIGERR_RETRY_LIMIT_IN_SEARCH_OVER);
+
+        let mut mp = MatchParam::default();
+        mp.set_retry_limit_in_match(u32::MAX);
+        mp.set_retry_limit_in_search(1000);
+        mp.set_cancel_flag(Arc::new(AtomicBool::new(false)));
+        let e = pathological_search(mp).unwrap_err();
+        assert_eq!(e.code(), onig_sys::ONIGERR_RETRY_LIMIT_IN_SEARCH_OVER);
+    }
+
+    fn pathological_search(mp: MatchParam) -> Result<Option<usize>, Error> {
+        let regex = Regex::new("(x+x+)+y").unwrap();
+        let text = "x".repeat(30);
+        regex.search_with_param(
+            text.as_str(),
+            0,
+            text.len(),
+            SearchOptions::SEARCH_OPTION_NONE,
+            None,
+            mp,
+        )
+    }
+
+    #[test]
+    pub fn deadline_stops_search() {
+        let mut mp = MatchParam::default();
+        mp.set_retry_limit_in_match(u32::MAX);
+        mp.set_deadline(Instant::now() + std::time::Duration::from_millis(5));
+        let e = pathological_search(mp).unwrap_err();
+        assert!(e.is_deadline_exceeded());
+        assert!(!e.is_cancelled());
+    }
+
+    #[test]
+    pub fn cancel_flag_stops_search() {
+        let mut mp = MatchParam::default();
+        mp.set_retry_limit_in_match(u32::MAX);
+        mp.set_cancel_flag(Arc::new(AtomicBool::new(true)));
+        let e = pathological_search(mp).unwrap_err();
+        assert!(e.is_cancelled());
+    }
+
+    #[test]
+    pub fn cancel_flag_checked_in_callouts() {
+        let regex = Regex::new("(?:a(?{x}))+b").unwrap();
+        let flag = Arc::new(AtomicBool::new(false));
+        let mut mp = MatchParam::default();
+        mp.set_cancel_flag(flag.clone());
+        mp.on_progress(move |args| {
+            if args.position() == 2 {
+                flag.store(true, Ordering::Relaxed);
+            }
+            CalloutResult::Success
+        });
+        let e = regex
+            .search_with_param("aaab", 0, 4, SearchOptions::SEARCH_OPTION_NONE, None, mp)
+            .unwrap_err();
+        assert!(e.is_cancelled());
+    }
+
+    #[test]
+    pub fn interruptible_search_still_matches() {
+        let regex = Regex::new("(a|b|ab)*bc").unwrap();
+        let mut mp = MatchParam::default();
+        mp.set_deadline(Instant::now() + std::time::Duration::from_secs(60));
+        mp.set_cancel_flag(Arc::new(AtomicBool::new(false)));
+        let res = regex.search_with_param(
+            "abababc",
+            0,
+            7,
+            SearchOptions::SEARCH_OPTION_NONE,
+            None,
+            &mp,
+        );
+        assert_eq!(res.unwrap(), Some(0));
+    }
 }
@@ -1,12 +1,73 @@
 use std::os::raw::{c_int, c_void};
 use std::slice;
 use std::str::from_utf8_unchecked;
+use std::sync::Arc;
 
 use onig_sys::{OnigRegex, OnigUChar};
 
-use super::Regex;
+use super::{Regex, Region};
+
+/// Named Group Table
+///
+/// The names of the capture groups of a regex, each with the groups
+/// which use it, ordered by the first group which uses each name.
+/// `Captures` keep a copy of the table of the regex they were matched
+/// with, so they can resolve names without borrowing the regex. The
+/// table is shared, and regexes without named groups don't allocate
+/// one.
+#[derive(Debug, Clone, Default)]
+pub(crate) struct NameTable(Option<Arc<[NameEntry]>>);
+
+/// A group name and the groups which use it.
+pub(crate) type NameEntry = (Box<str>, Box<[usize]>);
+
+impl NameTable {
+    /// Build the table of named groups of `regex`.
+    pub(crate) fn new(regex: &Regex) -> NameTable {
+        if regex.capture_names_len() == 0 {
+            return NameTable::default();
+        }
+        let mut names: Vec<NameEntry> = Vec::with_capacity(regex.capture_names_len());
+        regex.foreach_name(|name, groups| {
+            names.push((
+                Box::from(name),
+                groups.iter().map(|&g| g as usize).collect(),
+            ));
+            true
+        });
+        names.sort_by_key(|(_, groups)| groups.first().copied());
+        NameTable(Some(names.into()))
+    }
+
+    /// Each name with the groups which use it, ordered by the first
+    /// group which uses each name.
+    pub(crate) fn entries(&self) -> &[NameEntry] {
+        self.0.as_deref().unwrap_or(&[])
+    }
+
+    /// Resolves a group name to a group number
+    ///
+    /// When a name is used for more than one group, the last one
+    /// which took part in the match in `region` is used, as a
+    /// backreference to the name would. If none of them did, the last
+    /// group with the name is returned.
+    pub(crate) fn group(&self, name: &str, region: &Region) -> Option<usize> {
+        let (_, groups) = self.entries().iter().find(|(n, _)| &**n == name)?;
+        groups
+            .iter()
+            .rev()
+            .find(|&&group| region.pos(group).is_some())
+            .or_else(|| groups.last())
+            .copied()
+    }
+}
 
 impl Regex {
+    /// Get the table of named groups for this regex.
+    pub(crate) fn name_table(&self) -> NameTable {
+        self.names.clone()
+    }
+
     /// Returns the number of named groups into regex.
     pub fn capture_names_len(&self) -> usize {
         unsafe { onig_sys::onig_number_of_names(self.raw) as usize }
@@ -88,4 +149,37 @@ mod tests {
             vec![("foo".into(), vec![1u32]), ("bar".into(), vec![2u32, 3])]
         );
     }
+
+    #[test]
+    fn test_regex_name_to_group() {
+        let regex = Regex::new("(?<foo>he)(?<bar>l+)(?<bar>o)?").unwrap();
+        let names = regex.name_table();
+        assert_eq!(
+            names.entries(),
+            &[
+                (Box::from("foo"), Box::from([1])),
+                (Box::from("bar"), Box::from([2, 3]))
+            ]
+        );
+        let region = Region::new();
+        assert_eq!(names.group("foo", &region), Some(1));
+        assert_eq!(names.group("bar", &region), Some(3));
+        assert_eq!(names.group("baz", &region), None);
+
+        let mut region = region;
+        regex.match_with_options(
+            "hell",
+            0,
+            SearchOptions::SEARCH_OPTION_NONE,
+            Some(&mut region),
+        );
+        assert_eq!(names.group("bar", &region), Some(2));
+        regex.match_with_options(
+            "hello",
+            0,
+            SearchOptions::SEARCH_OPTION_NONE,
+            Some(&mut region),
+        );
+        assert_eq!(names.group("bar", &region), Some(3));
+    }
 }
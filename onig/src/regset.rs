@@ -0,0 +1,514 @@
+//! Regex Sets
+//!
+//! Contains the definition of `RegexSet`, a wrapper around the
+//! Oniguruma `OnigRegSet` API. A regex set searches a string for
+//! several patterns at once and reports which of them matched.
+
+use std::borrow::Borrow;
+use std::os::raw::c_int;
+use std::ptr::null_mut;
+use std::slice;
+
+use super::{Captures, EncodedChars, Error, ErrorKind, MatchParam, Regex, Region, SearchOptions};
+
+/// Regex Set Search Priority
+///
+/// Controls which match is reported when more than one of the
+/// patterns in a `RegexSet` matches the string being searched.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum RegSetLead {
+    /// Walk the string, trying every pattern at each position. The
+    /// match which starts earliest is reported. If more than one
+    /// pattern matches at that position the one added to the set
+    /// first wins.
+    Position,
+    /// Search the string with each pattern in turn and report the
+    /// match which starts earliest. If more than one pattern matches
+    /// at that position the one added to the set first wins. This is
+    /// usually faster than `Position` for a small number of patterns.
+    Regex,
+    /// Search the whole string with each pattern in turn, in the
+    /// order they were added to the set. The first pattern to match
+    /// anywhere is reported.
+    PriorityToRegexOrder,
+}
+
+impl RegSetLead {
+    fn as_raw(self) -> onig_sys::OnigRegSetLead {
+        match self {
+            RegSetLead::Position => onig_sys::OnigRegSetLead_ONIG_REGSET_POSITION_LEAD,
+            RegSetLead::Regex => onig_sys::OnigRegSetLead_ONIG_REGSET_REGEX_LEAD,
+            RegSetLead::PriorityToRegexOrder => {
+                onig_sys::OnigRegSetLead_ONIG_REGSET_PRIORITY_TO_REGEX_ORDER
+            }
+        }
+    }
+}
+
+/// A Set of Regular Expressions
+///
+/// Owns a list of compiled `Regex` values which can be searched for
+/// together in a single pass over a string. Each search reports the
+/// index of the pattern which matched, along with the match position.
+///
+/// All of the regexes in a set must share the same encoding, and
+/// none of them may be compiled with `REGEX_OPTION_FIND_LONGEST`.
+///
+/// # Examples
+///
+/// ```
+/// use onig::{Regex, RegexSet, RegSetLead};
+///
+/// let mut set = RegexSet::new(vec![
+///     Regex::new(r"\d+").unwrap(),
+///     Regex::new(r"[a-z]+").unwrap(),
+/// ]).unwrap();
+///
+/// let (idx, caps) = set.captures("  hello 42", RegSetLead::Position).unwrap();
+/// assert_eq!(idx, 1);
+/// assert_eq!(caps.at(0), Some("hello"));
+/// ```
+#[derive(Debug)]
+pub struct RegexSet {
+    raw: *mut onig_sys::OnigRegSet,
+    regexes: Vec<Regex>,
+}
+
+unsafe impl Send for RegexSet {}
+unsafe impl Sync for RegexSet {}
+
+impl RegexSet {
+    /// Create a Regex Set
+    ///
+    /// Builds a new set from the given regexes. The set takes
+    /// ownership of each regex. Patterns are given indices in the
+    /// order they are yielded.
+    ///
+    /// # Arguments
+    ///
+    ///  * `regexes` - The compiled patterns to place in the set.
+    ///
+    /// # Returns
+    ///
+    /// The new set, or an `Error` if one of the regexes can't be
+    /// added. This happens if the encodings don't agree or a regex
+    /// was compiled with `REGEX_OPTION_FIND_LONGEST`.
+    pub fn new<I>(regexes: I) -> Result<RegexSet, Error>
+    where
+        I: IntoIterator<Item = Regex>,
+    {
+        let mut raw: *mut onig_sys::OnigRegSet = null_mut();
+        let err = unsafe { onig_sys::onig_regset_new(&mut raw, 0, null_mut()) };
+        if err != onig_sys::ONIG_NORMAL as i32 {
+            return Err(Error::from_code(err));
+        }
+
+        let mut set = RegexSet {
+            raw,
+            regexes: Vec::new(),
+        };
+        for regex in regexes {
+            set.add(regex)?;
+        }
+        Ok(set)
+    }
+
+    /// Add a Regex to the Set
+    ///
+    /// Appends `regex` to the end of the set. It is given the next
+    /// available index.
+    pub fn add(&mut self, regex: Regex) -> Result<(), Error> {
+        let err = unsafe { onig_sys::onig_regset_add(self.raw, regex.raw) };
+        if err != onig_sys::ONIG_NORMAL as i32 {
+            return Err(Error::from_code(err));
+        }
+        self.regexes.push(regex);
+        Ok(())
+    }
+
+    /// Replace a Regex in the Set
+    ///
+    /// Swaps the regex at `index` for `regex`. The regex which was
+    /// previously at that index is returned to the caller.
+    pub fn replace(&mut self, index: usize, regex: Regex) -> Result<Regex, Error> {
+        if index >= self.regexes.len() {
+            return Err(Error::custom(
+                ErrorKind::InvalidArgument,
+                format!(
+                    "Index {} is out of range for regex set of length {}",
+                    index,
+                    self.regexes.len()
+                ),
+            ));
+        }
+        let err = unsafe { onig_sys::onig_regset_replace(self.raw, index as c_int, regex.raw) };
+        if err != onig_sys::ONIG_NORMAL as i32 {
+            return Err(Error::from_code(err));
+        }
+        Ok(std::mem::replace(&mut self.regexes[index], regex))
+    }
+
+    /// Get the Number of Regexes in the Set
+    pub fn len(&self) -> usize {
+        self.regexes.len()
+    }
+
+    /// Check if the Set Contains no Regexes
+    pub fn is_empty(&self) -> bool {
+        self.regexes.is_empty()
+    }
+
+    /// Get the Regex at the given Index
+    pub fn get(&self, index: usize) -> Option<&Regex> {
+        self.regexes.get(index)
+    }
+
+    /// Iterate Over the Regexes in the Set
+    pub fn iter(&self) -> slice::Iter<'_, Regex> {
+        self.regexes.iter()
+    }
+
+    /// Search for any Pattern in a String
+    ///
+    /// Searches `str` between the byte offsets `from` and `to` for a
+    /// match of any of the patterns in the set. This method works the
+    /// same way as `search_with_encoding`, but the encoding is always
+    /// utf-8.
+    ///
+    /// # Returns
+    ///
+    /// `Some((idx, pos))` if a pattern matched, where `idx` is the
+    /// index of the pattern in the set and `pos` is the byte position
+    /// of the start of the match. `None` if nothing matched.
+    pub fn search_with_options(
+        &mut self,
+        str: &str,
+        from: usize,
+        to: usize,
+        lead: RegSetLead,
+        options: SearchOptions,
+    ) -> Option<(usize, usize)> {
+        self.search_with_encoding(str, from, to, lead, options)
+    }
+
+    /// Search for any Pattern in a Buffer with an Encoding
+    ///
+    /// Searches `chars` between the byte offsets `from` and `to` for a
+    /// match of any of the patterns in the set. The encoding of the
+    /// buffer must match the encoding of the regexes in the set.
+    ///
+    /// Capture information for the match can be retrieved with
+    /// `RegexSet::region` until the next search.
+    ///
+    /// # Returns
+    ///
+    /// `Some((idx, pos))` if a pattern matched, where `idx` is the
+    /// index of the pattern in the set and `pos` is the byte position
+    /// of the start of the match. `None` if nothing matched.
+    pub fn search_with_encoding<T>(
+        &mut self,
+        chars: T,
+        from: usize,
+        to: usize,
+        lead: RegSetLead,
+        options: SearchOptions,
+    ) -> Option<(usize, usize)>
+    where
+        T: EncodedChars,
+    {
+        let match_param = MatchParam::default();
+        let result = self.search_with_param(chars, from, to, lead, options, match_param);
+
+        match result {
+            Ok(r) => r,
+            Err(e) => panic!("Onig: RegexSet search error: {}", e.description()),
+        }
+    }
+
+    /// Search for any Pattern with a Match Param
+    ///
+    /// Searches `chars` between the byte offsets `from` and `to` for a
+    /// match of any of the patterns in the set. The given
+    /// `match_param`, which can be owned or borrowed, is applied to
+    /// every pattern in the set.
+    ///
+    /// # Returns
+    ///
+    /// `Ok(Some((idx, pos)))` if a pattern matched, where `idx` is the
+    /// index of the pattern in the set and `pos` is the byte position
+    /// of the start of the match. `Ok(None)` if nothing matched. `Err`
+    /// with an `Error` if an error occurred (e.g. retry-limit-in-match
+    /// exceeded).
+    pub fn search_with_param<T, P>(
+        &mut self,
+        chars: T,
+        from: usize,
+        to: usize,
+        lead: RegSetLead,
+        options: SearchOptions,
+        match_param: P,
+    ) -> Result<Option<(usize, usize)>, Error>
+    where
+        T: EncodedChars,
+        P: Borrow<MatchParam>,
+    {
+        let match_param = match_param.borrow();
+        // Oniguruma always fills in a region for each regex in the set.
+        options.check(true)?;
+        let encoding = match self.regexes.first() {
+            Some(regex) => regex.encoding(),
+            None => return Ok(None),
+        };
+        if chars.encoding() != encoding {
+            return Err(Error::custom(
+                ErrorKind::EncodingMismatch,
+                format!(
+                    "Regex encoding does not match haystack encoding ({0:?}, {1:?})",
+                    chars.encoding(),
+                    encoding
+                ),
+            ));
+        }
+
+        let (beg, end) = (chars.start_ptr(), chars.limit_ptr());
+        let mut params = vec![match_param.as_raw(); self.regexes.len()];
+        let mut match_pos: c_int = 0;
+        let (start, range) = unsafe { (beg.add(from), beg.add(to)) };
+        if start > end {
+            return Err(Error::custom(
+                ErrorKind::InvalidArgument,
+                "Start of match should be before end",
+            ));
+        }
+        if range > end {
+            return Err(Error::custom(
+                ErrorKind::InvalidArgument,
+                "Limit of match should be before end",
+            ));
+        }
+        let r = match_param.run(|| unsafe {
+            onig_sys::onig_regset_search_with_param(
+                self.raw,
+                beg,
+                end,
+                start,
+                range,
+                lead.as_raw(),
+                options.bits(),
+                params.as_mut_ptr(),
+                &mut match_pos,
+            )
+        })?;
+
+        if r >= 0 {
+            Ok(Some((r as usize, match_pos as usize)))
+        } else if r == onig_sys::ONIG_MISMATCH {
+            Ok(None)
+        } else {
+            Err(Error::from_code(r))
+        }
+    }
+
+    /// Get the Region for a Pattern in the Set
+    ///
+    /// Returns a copy of the capture information recorded for the
+    /// pattern at `index` by the most recent search.
+    pub fn region(&self, index: usize) -> Option<Region> {
+        if index >= self.regexes.len() {
+            return None;
+        }
+        let raw = unsafe { onig_sys::onig_regset_get_region(self.raw, index as c_int) };
+        if raw.is_null() {
+            None
+        } else {
+            Some(unsafe { Region::clone_from_raw(raw) })
+        }
+    }
+
+    /// Find the First Match of any Pattern
+    ///
+    /// Searches the whole of `text` for a match of any of the patterns
+    /// in the set.
+    ///
+    /// # Returns
+    ///
+    /// The index of the pattern which matched, along with the start
+    /// and end offsets of the match. If no match exists `None` is
+    /// returned.
+    pub fn find(&mut self, text: &str, lead: RegSetLead) -> Option<(usize, (usize, usize))> {
+        self.captures(text, lead)
+            .and_then(|(idx, caps)| caps.pos(0).map(|pos| (idx, pos)))
+    }
+
+    /// Capture the First Match of any Pattern
+    ///
+    /// Searches the whole of `text` for a match of any of the patterns
+    /// in the set.
+    ///
+    /// # Returns
+    ///
+    /// The index of the pattern which matched, along with the capture
+    /// groups of that pattern for the match. If no match exists `None`
+    /// is returned.
+    pub fn captures<'t>(
+        &mut self,
+        text: &'t str,
+        lead: RegSetLead,
+    ) -> Option<(usize, Captures<'t>)> {
+        let (idx, pos) =
+            self.search_with_options(text, 0, text.len(), lead, SearchOptions::SEARCH_OPTION_NONE)?;
+        let region = self.region(idx)?;
+        Some((
+            idx,
+            Captures {
+                names: self.regexes[idx].name_table(),
+                text,
+                region,
+                offset: pos,
+            },
+        ))
+    }
+}
+
+impl<'a> IntoIterator for &'a RegexSet {
+    type Item = &'a Regex;
+    type IntoIter = slice::Iter<'a, Regex>;
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+impl Drop for RegexSet {
+    fn drop(&mut self) {
+        unsafe {
+            // `onig_regset_free` frees the regexes in the set too. The
+            // regexes are owned by `self.regexes`, so remove them from
+            // the raw set first to avoid freeing them twice.
+            for idx in (0..self.regexes.len()).rev() {
+                onig_sys::onig_regset_replace(self.raw, idx as c_int, null_mut());
+            }
+            onig_sys::onig_regset_free(self.raw);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::*;
+
+    fn numbers_and_words() -> RegexSet {
+        RegexSet::new(vec![
+            Regex::new(r"\d+").unwrap(),
+            Regex::new(r"([a-z])[a-z]*").unwrap(),
+        ])
+        .unwrap()
+    }
+
+    #[test]
+    fn test_regset_create() {
+        let set = numbers_and_words();
+        assert_eq!(set.len(), 2);
+        assert!(!set.is_empty());
+        assert_eq!(
+            set.iter().map(|r| r.captures_len()).collect::<Vec<_>>(),
+            vec![0, 1]
+        );
+    }
+
+    #[test]
+    fn test_regset_empty_never_matches() {
+        let mut set = RegexSet::new(Vec::new()).unwrap();
+        assert!(set.is_empty());
+        assert_eq!(set.find("hello", RegSetLead::Position), None);
+    }
+
+    #[test]
+    fn test_regset_position_lead() {
+        let mut set = numbers_and_words();
+        assert_eq!(
+            set.find("  hello 42", RegSetLead::Position),
+            Some((1, (2, 7)))
+        );
+        assert_eq!(
+            set.find("  42 hello", RegSetLead::Position),
+            Some((0, (2, 4)))
+        );
+        assert_eq!(set.find("  !!!", RegSetLead::Position), None);
+    }
+
+    #[test]
+    fn test_regset_priority_to_regex_order() {
+        let mut set = numbers_and_words();
+        assert_eq!(
+            set.find("  hello 42", RegSetLead::PriorityToRegexOrder),
+            Some((0, (8, 10)))
+        );
+    }
+
+    #[test]
+    fn test_regset_captures() {
+        let mut set = numbers_and_words();
+        let (idx, caps) = set.captures("123 hello", RegSetLead::Regex).unwrap();
+        assert_eq!(idx, 0);
+        assert_eq!(caps.at(0), Some("123"));
+        assert_eq!(caps.len(), 1);
+
+        let (idx, caps) = set.captures("!! hello", RegSetLead::Regex).unwrap();
+        assert_eq!(idx, 1);
+        assert_eq!(caps.offset(), 3);
+        assert_eq!(caps.at(0), Some("hello"));
+        assert_eq!(caps.at(1), Some("h"));
+    }
+
+    #[test]
+    fn test_regset_captures_outlive_set() {
+        let captures = {
+            let mut set = RegexSet::new(vec![Regex::new(r"(?<word>[a-z]+)").unwrap()]).unwrap();
+            set.captures("42 hello", RegSetLead::Position).unwrap().1
+        };
+        assert_eq!(captures.name("word"), Some("hello"));
+    }
+
+    #[test]
+    fn test_regset_add_and_replace() {
+        let mut set = numbers_and_words();
+        set.add(Regex::new("!+").unwrap()).unwrap();
+        assert_eq!(set.len(), 3);
+        assert_eq!(set.find("  !!!", RegSetLead::Position), Some((2, (2, 5))));
+
+        let old = set.replace(0, Regex::new("x").unwrap()).unwrap();
+        assert_eq!(old.find("a12"), Some((1, 3)));
+        assert_eq!(set.find("12 x", RegSetLead::Position), Some((0, (3, 4))));
+
+        assert!(set.replace(3, Regex::new("y").unwrap()).is_err());
+    }
+
+    #[test]
+    fn test_regset_rejects_find_longest() {
+        let longest = Regex::with_options(
+            "a+",
+            RegexOptions::REGEX_OPTION_FIND_LONGEST,
+            Syntax::default(),
+        )
+        .unwrap();
+        let e = RegexSet::new(vec![longest]).unwrap_err();
+        assert_eq!(e.code(), onig_sys::ONIGERR_INVALID_ARGUMENT);
+    }
+
+    #[test]
+    fn test_regset_encoding_mismatch() {
+        let mut set = RegexSet::new(vec![
+            Regex::with_encoding(EncodedBytes::ascii(b"a")).unwrap()
+        ])
+        .unwrap();
+        let res = set.search_with_param(
+            "a",
+            0,
+            1,
+            RegSetLead::Position,
+            SearchOptions::SEARCH_OPTION_NONE,
+            MatchParam::default(),
+        );
+        assert!(res.is_err());
+    }
+}
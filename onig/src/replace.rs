@@ -3,11 +3,11 @@ use std::borrow::Cow;
 
 /// Replacer describes types that can be used to replace matches in a string.
 ///
-/// Implementations are provided for replacement using string literals
-/// and `FnMut` callbacks. If this isn't enough for your replacement
-/// needs a user-supplied `Replacer` implemenation can be
-/// provided. For an example of a custom replacer implementation check
-/// out `examples/dollar.rs` in the Onig crate.
+/// Implementations are provided for replacement using string literals,
+/// `NoExpand`, `Template`s and `FnMut` callbacks. If this isn't enough
+/// for your replacement needs a user-supplied `Replacer` implemenation
+/// can be provided. For an example of a custom replacer implementation
+/// check out `examples/dollar.rs` in the Onig crate.
 pub trait Replacer {
     /// Returns a possibly owned string that is used to replace the match
     /// corresponding to the `caps` capture group.
@@ -31,6 +31,205 @@ where
     }
 }
 
+/// Literal Replacement
+///
+/// Replaces each match with the wrapped string as-is. Plain `&str`
+/// replacements are already literal; this wrapper makes that explicit
+/// in code which also uses `Template`s.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct NoExpand<'t>(pub &'t str);
+
+/// Replacement using `NoExpand` Literals
+impl Replacer for NoExpand<'_> {
+    fn reg_replace(&mut self, _: &Captures) -> Cow<'_, str> {
+        self.0.into()
+    }
+}
+
+/// Replacement Template
+///
+/// A replacement string which refers to the capture groups of each
+/// match. The template is parsed once when it is created and can then
+/// be used to expand any number of matches. The following sequences
+/// are recognised:
+///
+///  * `$N` and `${N}` - the text of the numbered group `N`.
+///  * `${name}` and `\k<name>` - the text of the named group `name`.
+///  * `$$` - a literal `$`, and `\\` - a literal `\`.
+///  * `\U` and `\L` - convert the rest of the replacement to upper
+///    or lower case, up to the next `\E`, `\U` or `\L`.
+///  * `\E` - end a `\U` or `\L` case conversion.
+///
+/// A reference to a group which doesn't exist or didn't take part in
+/// the match expands to nothing. Anything else is copied literally.
+///
+/// # Examples
+///
+/// ```rust
+/// # use onig::{Regex, Template};
+/// let re = Regex::new(r"(?<last>[^,\s]+),\s+(?<first>\S+)").unwrap();
+/// let template = Template::new(r"${first} \U\k<last>\E costs $$5");
+/// assert_eq!(
+///     re.replace("Springsteen, Bruce", &template),
+///     "Bruce SPRINGSTEEN costs $5"
+/// );
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Template {
+    pieces: Vec<TemplatePiece>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum TemplatePiece {
+    Literal(String),
+    Group(usize),
+    Name(String),
+    Case(CaseConversion),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum CaseConversion {
+    None,
+    Upper,
+    Lower,
+}
+
+impl Template {
+    /// Parse a Replacement Template
+    ///
+    /// Parses `template` into a form which can be expanded quickly for
+    /// each match. Parsing never fails: sequences which aren't valid
+    /// references are kept as literal text.
+    pub fn new(template: &str) -> Template {
+        let mut pieces = Vec::new();
+        let mut literal = String::new();
+        let mut rest = template;
+        while let Some(c) = rest.chars().next() {
+            let after = &rest[c.len_utf8()..];
+            let parsed = match c {
+                '$' => parse_dollar(after),
+                '\\' => parse_backslash(after),
+                _ => None,
+            };
+            match parsed {
+                Some((TemplatePiece::Literal(text), len)) => {
+                    literal.push_str(&text);
+                    rest = &after[len..];
+                }
+                Some((piece, len)) => {
+                    if !literal.is_empty() {
+                        pieces.push(TemplatePiece::Literal(literal.split_off(0)));
+                    }
+                    pieces.push(piece);
+                    rest = &after[len..];
+                }
+                None => {
+                    literal.push(c);
+                    rest = after;
+                }
+            }
+        }
+        if !literal.is_empty() {
+            pieces.push(TemplatePiece::Literal(literal));
+        }
+        Template { pieces }
+    }
+
+    /// Expand the Template
+    ///
+    /// Appends the expansion of this template for the match `caps` to
+    /// `dst`.
+    pub fn expand(&self, caps: &Captures, dst: &mut String) {
+        let mut case = CaseConversion::None;
+        for piece in &self.pieces {
+            let text = match *piece {
+                TemplatePiece::Literal(ref text) => text.as_str(),
+                TemplatePiece::Group(group) => caps.at(group).unwrap_or(""),
+                TemplatePiece::Name(ref name) => caps.name(name).unwrap_or(""),
+                TemplatePiece::Case(conversion) => {
+                    case = conversion;
+                    continue;
+                }
+            };
+            match case {
+                CaseConversion::None => dst.push_str(text),
+                CaseConversion::Upper => dst.push_str(&text.to_uppercase()),
+                CaseConversion::Lower => dst.push_str(&text.to_lowercase()),
+            }
+        }
+    }
+}
+
+impl From<&str> for Template {
+    fn from(template: &str) -> Template {
+        Template::new(template)
+    }
+}
+
+/// Parse the sequence following a `$`, returning the piece and the
+/// number of bytes it used.
+fn parse_dollar(after: &str) -> Option<(TemplatePiece, usize)> {
+    if after.starts_with('$') {
+        return Some((TemplatePiece::Literal("$".into()), 1));
+    }
+    if after.starts_with('{') {
+        let end = after.find('}')?;
+        return group_reference(&after[1..end]).map(|piece| (piece, end + 1));
+    }
+    let digits = after
+        .find(|c: char| !c.is_ascii_digit())
+        .unwrap_or(after.len());
+    let group = after[..digits].parse().ok()?;
+    Some((TemplatePiece::Group(group), digits))
+}
+
+/// Parse the sequence following a `\`, returning the piece and the
+/// number of bytes it used.
+fn parse_backslash(after: &str) -> Option<(TemplatePiece, usize)> {
+    let piece = match after.chars().next()? {
+        '\\' => TemplatePiece::Literal("\\".into()),
+        'U' => TemplatePiece::Case(CaseConversion::Upper),
+        'L' => TemplatePiece::Case(CaseConversion::Lower),
+        'E' => TemplatePiece::Case(CaseConversion::None),
+        'k' if after[1..].starts_with('<') => {
+            let end = after.find('>')?;
+            return group_reference(&after[2..end]).map(|piece| (piece, end + 1));
+        }
+        _ => return None,
+    };
+    Some((piece, 1))
+}
+
+/// Resolve the contents of `${...}` or `\k<...>` to a group number or
+/// name.
+fn group_reference(reference: &str) -> Option<TemplatePiece> {
+    if reference.is_empty() {
+        None
+    } else if reference.bytes().all(|b| b.is_ascii_digit()) {
+        reference.parse().ok().map(TemplatePiece::Group)
+    } else {
+        Some(TemplatePiece::Name(reference.into()))
+    }
+}
+
+/// Replacement using `Template`s
+impl Replacer for Template {
+    fn reg_replace(&mut self, caps: &Captures) -> Cow<'_, str> {
+        let mut expanded = String::new();
+        self.expand(caps, &mut expanded);
+        expanded.into()
+    }
+}
+
+/// Replacement using borrowed `Template`s
+impl Replacer for &Template {
+    fn reg_replace(&mut self, caps: &Captures) -> Cow<'_, str> {
+        let mut expanded = String::new();
+        self.expand(caps, &mut expanded);
+        expanded.into()
+    }
+}
+
 impl Regex {
     /// Replaces the leftmost-first match with the replacement provided.
     /// The replacement can be a regular string or a function that takes
@@ -66,6 +265,9 @@ impl Regex {
     /// assert_eq!(result, "Bruce Springsteen");
     /// # }
     /// ```
+    ///
+    /// To refer to submatches from a replacement string use a
+    /// `Template`. Plain strings are always inserted literally.
     pub fn replace<R: Replacer>(&self, text: &str, rep: R) -> String {
         self.replacen(text, 1, rep)
     }
@@ -103,3 +305,67 @@ impl Regex {
         new
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::super::*;
+
+    fn expand(pattern: &str, template: &str, text: &str) -> String {
+        Regex::new(pattern)
+            .unwrap()
+            .replace_all(text, Template::new(template))
+    }
+
+    #[test]
+    fn test_template_numbered() {
+        assert_eq!(
+            expand(r"(\w+) (\w+)", "$2 $1", "hello world"),
+            "world hello"
+        );
+        assert_eq!(expand(r"(\w+) (\w+)", "${2}1", "hello world"), "world1");
+        assert_eq!(expand(r"(a)|(b)", "[$2$3]", "ab"), "[][b]");
+        assert_eq!(expand(r"\d", "<$0>", "a1b2"), "a<1>b<2>");
+    }
+
+    #[test]
+    fn test_template_named() {
+        let pattern = r"(?<foo>he)(?<bar>l+)(?<bar>o)?";
+        assert_eq!(expand(pattern, "${bar}-${foo}", "hello"), "o-he");
+        assert_eq!(expand(pattern, r"\k<bar>-\k<foo>", "hell"), "ll-he");
+        assert_eq!(expand(pattern, "${baz}!", "hello"), "!");
+    }
+
+    #[test]
+    fn test_template_literals() {
+        assert_eq!(expand(r"\d+", "$$", "a12"), "a$");
+        assert_eq!(expand(r"\d+", "$x ${ $", "a12"), "a$x ${ $");
+        assert_eq!(expand(r"\d+", r"\\U \n \k<", "a12"), r"a\U \n \k<");
+    }
+
+    #[test]
+    fn test_template_case_conversion() {
+        let pattern = r"(\w+) (\w+)";
+        assert_eq!(expand(pattern, r"\U$1\E $2", "hello World"), "HELLO World");
+        assert_eq!(
+            expand(pattern, r"\L$2 \Ux$1", "hello World"),
+            "world XHELLO"
+        );
+        assert_eq!(expand(pattern, r"\U${2}ß", "hello world"), "WORLDSS");
+    }
+
+    #[test]
+    fn test_template_reuse() {
+        let re = Regex::new(r"(\d+)").unwrap();
+        let template = Template::new("<$1>");
+        assert_eq!(re.replace_all("1 2", &template), "<1> <2>");
+        assert_eq!(re.replace("3 4", &template), "<3> 4");
+        assert_eq!(Template::from("<$1>"), template);
+    }
+
+    #[test]
+    fn test_no_expand() {
+        let re = Regex::new(r"(\w+)").unwrap();
+        assert_eq!(re.replace("hello", NoExpand("$1")), "$1");
+        assert_eq!(re.replace("hello", "$1"), "$1");
+    }
+}
@@ -0,0 +1,476 @@
+//! Stream Searching
+//!
+//! Contains the definition of `StreamSearcher`, which searches text
+//! read incrementally from an `io::Read`, and `StreamCaptures`, the
+//! owned capture groups it yields. Only a window of the stream is
+//! held in memory at once, so arbitrarily large inputs can be
+//! searched.
+
+use std::io::{self, Read};
+use std::iter::FusedIterator;
+use std::mem;
+
+use super::buffers::char_len;
+use super::names::NameTable;
+use super::{EncodedBytes, Regex, Region, SearchOptions};
+
+/// The number of bytes read from the stream at a time, by default.
+const DEFAULT_CHUNK_SIZE: usize = 64 * 1024;
+
+/// The number of bytes kept before the search position, by default.
+const DEFAULT_LOOK_BEHIND: usize = 1024;
+
+/// The longest match which is guaranteed to be found, by default.
+const DEFAULT_MAX_MATCH_LEN: usize = 64 * 1024;
+
+/// Stream Searcher
+///
+/// An iterator over the non-overlapping matches of a regex in text
+/// read from an `io::Read`. Each match is yielded as a
+/// `StreamCaptures`, which owns the matched text and reports absolute
+/// byte offsets into the stream.
+///
+/// The stream is read in chunks and searched from a buffer which keeps
+/// `look_behind` bytes before the current search position, for
+/// look-behind assertions, `\b` and `^`. A match is only reported once
+/// at least `max_match_len` bytes following its start are in the
+/// buffer, so matches which cross chunk boundaries are found just as
+/// if the whole stream had been searched at once, as long as each
+/// match, along with any look-ahead it needs, fits in `max_match_len`
+/// bytes.
+///
+/// The stream is interpreted using the encoding of the regex. Errors
+/// reading the stream, and errors from Oniguruma such as a retry limit
+/// being exceeded, are yielded as `io::Error`s, after which the
+/// iterator ends.
+///
+/// # Examples
+///
+/// ```
+/// use onig::Regex;
+///
+/// let regex = Regex::new(r"ERROR (\w+)").unwrap();
+/// let log = "INFO start\nERROR disk\nINFO retry\nERROR network\n";
+/// let errors: Vec<(u64, Vec<u8>)> = regex
+///     .search_stream(log.as_bytes())
+///     .chunk_size(8)
+///     .map(|caps| {
+///         let caps = caps.unwrap();
+///         (caps.pos(0).unwrap().0, caps.at(1).unwrap().to_vec())
+///     })
+///     .collect();
+/// assert_eq!(
+///     errors,
+///     vec![(11, b"disk".to_vec()), (33, b"network".to_vec())]
+/// );
+/// ```
+#[derive(Debug)]
+pub struct StreamSearcher<'r, R> {
+    regex: &'r Regex,
+    reader: R,
+    chunk_size: usize,
+    look_behind: usize,
+    max_match_len: usize,
+    buffer: Vec<u8>,
+    buffer_offset: u64,
+    pos: usize,
+    last_match_end: Option<usize>,
+    eof: bool,
+    done: bool,
+    region: Region,
+}
+
+impl<'r, R: Read> StreamSearcher<'r, R> {
+    /// Create a Stream Searcher
+    ///
+    /// Searches the text read from `reader` for `regex`, using the
+    /// default chunk size, look-behind and maximum match length.
+    ///
+    /// # Arguments
+    ///
+    ///  * `regex` - The regex to search for.
+    ///  * `reader` - The stream to read the text from.
+    pub fn new(regex: &'r Regex, reader: R) -> Self {
+        StreamSearcher {
+            regex,
+            reader,
+            chunk_size: DEFAULT_CHUNK_SIZE,
+            look_behind: DEFAULT_LOOK_BEHIND,
+            max_match_len: DEFAULT_MAX_MATCH_LEN,
+            buffer: Vec::new(),
+            buffer_offset: 0,
+            pos: 0,
+            last_match_end: None,
+            eof: false,
+            done: false,
+            region: Region::new(),
+        }
+    }
+
+    /// Set the number of bytes read from the stream at a time.
+    pub fn chunk_size(mut self, size: usize) -> Self {
+        self.chunk_size = size.max(1);
+        self
+    }
+
+    /// Set the number of bytes kept before the search position
+    ///
+    /// Look-behind assertions, `\b` and `^` can see this far back
+    /// before the end of the previous match. At least one character is
+    /// always kept.
+    pub fn look_behind(mut self, len: usize) -> Self {
+        self.look_behind = len;
+        self
+    }
+
+    /// Set the maximum match length
+    ///
+    /// Matches up to this many bytes long, including any look-ahead,
+    /// are found correctly even when they cross a chunk boundary.
+    /// Longer matches may be cut short. The buffer grows to hold this
+    /// many bytes, plus the look-behind and a chunk.
+    pub fn max_match_len(mut self, len: usize) -> Self {
+        self.max_match_len = len.max(1);
+        self
+    }
+
+    /// Get the underlying reader.
+    pub fn get_ref(&self) -> &R {
+        &self.reader
+    }
+
+    /// Consume the searcher, returning the underlying reader.
+    pub fn into_inner(self) -> R {
+        self.reader
+    }
+
+    /// Search the buffer from the current position for a match starting
+    /// at or before `limit`.
+    ///
+    /// Oniguruma doesn't let a forward search match past the end of its
+    /// search range, so the whole buffer is searched and matches which
+    /// start after `limit` are ignored.
+    fn search(&mut self, limit: usize) -> Result<Option<(usize, usize)>, io::Error> {
+        let mut options = SearchOptions::SEARCH_OPTION_NONE;
+        if self.buffer_offset > 0 {
+            options |= SearchOptions::SEARCH_OPTION_NOT_BEGIN_STRING;
+        }
+        if !self.eof {
+            options |=
+                SearchOptions::SEARCH_OPTION_NOTEOL | SearchOptions::SEARCH_OPTION_NOT_END_STRING;
+        }
+        self.region.clear();
+        let text = EncodedBytes::from_parts(&self.buffer, self.regex.encoding());
+        let found = self
+            .regex
+            .search_with_param(
+                text,
+                self.pos,
+                self.buffer.len(),
+                options,
+                Some(&mut self.region),
+                self.regex.match_param(),
+            )
+            .map_err(|e| io::Error::new(io::ErrorKind::Other, e))?;
+        Ok(found
+            .filter(|&start| start <= limit)
+            .map(|_| self.region.pos(0).unwrap()))
+    }
+
+    /// Discard the text before the look-behind window and read the next
+    /// chunk of the stream into the buffer.
+    fn fill(&mut self) -> io::Result<()> {
+        let keep = self.char_head(self.pos.saturating_sub(self.look_behind.max(1)));
+        if keep > 0 {
+            self.buffer.drain(..keep);
+            self.buffer_offset += keep as u64;
+            self.pos -= keep;
+            self.last_match_end = self.last_match_end.and_then(|e| e.checked_sub(keep));
+        }
+
+        let len = self.buffer.len();
+        self.buffer.resize(len + self.chunk_size, 0);
+        loop {
+            match self.reader.read(&mut self.buffer[len..]) {
+                Ok(n) => {
+                    self.buffer.truncate(len + n);
+                    self.eof = n == 0;
+                    return Ok(());
+                }
+                Err(ref e) if e.kind() == io::ErrorKind::Interrupted => {}
+                Err(e) => {
+                    self.buffer.truncate(len);
+                    return Err(e);
+                }
+            }
+        }
+    }
+
+    /// Move `pos` back to the start of the character containing it.
+    fn char_head(&self, pos: usize) -> usize {
+        let start = self.buffer.as_ptr();
+        let head = unsafe {
+            onig_sys::onigenc_get_left_adjust_char_head(
+                self.regex.encoding().as_raw(),
+                start,
+                start.add(pos),
+            )
+        };
+        head as usize - start as usize
+    }
+
+    /// Take the captures of the last match out of the searcher.
+    fn take_captures(&mut self, start: usize, end: usize) -> StreamCaptures {
+        let region = mem::take(&mut self.region);
+        let (text_start, text_end) = (0..region.len())
+            .filter_map(|group| region.pos(group))
+            .fold((start, end), |(s, e), (gs, ge)| (s.min(gs), e.max(ge)));
+        StreamCaptures {
+            names: self.regex.name_table(),
+            text: self.buffer[text_start..text_end].to_vec(),
+            text_start,
+            buffer_offset: self.buffer_offset,
+            region,
+        }
+    }
+}
+
+impl<'r, R: Read> Iterator for StreamSearcher<'r, R> {
+    type Item = io::Result<StreamCaptures>;
+
+    fn next(&mut self) -> Option<io::Result<StreamCaptures>> {
+        while !self.done {
+            let end = self.buffer.len();
+            let limit = if self.eof {
+                Some(end)
+            } else {
+                end.checked_sub(self.max_match_len)
+            };
+
+            match limit {
+                Some(limit) if self.pos <= limit => match self.search(limit) {
+                    Ok(Some((s, e))) => {
+                        // Don't accept empty matches immediately following
+                        // the last match.
+                        if e == s && self.last_match_end == Some(e) {
+                            let enc = self.regex.encoding();
+                            self.pos += char_len(enc, &self.buffer[self.pos..]);
+                            continue;
+                        }
+                        self.pos = e;
+                        self.last_match_end = Some(e);
+                        return Some(Ok(self.take_captures(s, e)));
+                    }
+                    Ok(None) if self.eof => self.done = true,
+                    // Every start up to `limit` has been tried.
+                    Ok(None) => self.pos = self.char_head(limit),
+                    Err(e) => {
+                        self.done = true;
+                        return Some(Err(e));
+                    }
+                },
+                _ if self.eof => self.done = true,
+                _ => {}
+            }
+
+            if !self.done {
+                if let Err(e) = self.fill() {
+                    self.done = true;
+                    return Some(Err(e));
+                }
+            }
+        }
+        None
+    }
+}
+
+impl<R: Read> FusedIterator for StreamSearcher<'_, R> {}
+
+/// Stream Captures
+///
+/// The capture groups of a match found by a `StreamSearcher`. The
+/// captured text is copied out of the stream's buffer, and positions
+/// are absolute byte offsets from the start of the stream.
+#[derive(Debug, Clone)]
+pub struct StreamCaptures {
+    names: NameTable,
+    text: Vec<u8>,
+    text_start: usize,
+    buffer_offset: u64,
+    region: Region,
+}
+
+impl StreamCaptures {
+    /// Returns the start and end positions of the Nth capture group.
+    /// Returns `None` if `pos` is not a valid capture group or if the
+    /// capture group did not match anything.
+    pub fn pos(&self, pos: usize) -> Option<(u64, u64)> {
+        self.region
+            .pos(pos)
+            .map(|(s, e)| (self.buffer_offset + s as u64, self.buffer_offset + e as u64))
+    }
+
+    /// Returns the matched bytes for the capture group `i`. If `i`
+    /// isn't a valid capture group or didn't match anything, then
+    /// `None` is returned.
+    pub fn at(&self, pos: usize) -> Option<&[u8]> {
+        self.region
+            .pos(pos)
+            .map(|(s, e)| &self.text[s - self.text_start..e - self.text_start])
+    }
+
+    /// Returns the start and end positions of the capture group named
+    /// `name`. If there are several groups with that name, the last
+    /// one which matched is used.
+    pub fn name_pos(&self, name: &str) -> Option<(u64, u64)> {
+        self.pos(self.names.group(name, &self.region)?)
+    }
+
+    /// Returns the matched bytes for the capture group named `name`.
+    pub fn name(&self, name: &str) -> Option<&[u8]> {
+        self.at(self.names.group(name, &self.region)?)
+    }
+
+    /// Returns the number of captured groups.
+    pub fn len(&self) -> usize {
+        self.region.len()
+    }
+
+    /// Returns true if and only if there are no captured groups.
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+}
+
+impl Regex {
+    /// Search a Stream
+    ///
+    /// Returns an iterator over the matches of this regex in the text
+    /// read from `reader`. See `StreamSearcher` for details of how the
+    /// stream is buffered, and the settings which control it.
+    ///
+    /// # Arguments
+    ///
+    ///  * `reader` - The stream to read the text from.
+    pub fn search_stream<R: Read>(&self, reader: R) -> StreamSearcher<'_, R> {
+        StreamSearcher::new(self, reader)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::*;
+    use std::io;
+
+    /// A reader which returns at most `step` bytes per read.
+    struct Trickle<'a> {
+        text: &'a [u8],
+        step: usize,
+    }
+
+    impl io::Read for Trickle<'_> {
+        fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+            let n = self.step.min(buf.len()).min(self.text.len());
+            buf[..n].copy_from_slice(&self.text[..n]);
+            self.text = &self.text[n..];
+            Ok(n)
+        }
+    }
+
+    fn stream_matches(pattern: &str, text: &str, chunk_size: usize) -> Vec<(u64, u64)> {
+        let regex = Regex::new(pattern).unwrap();
+        let reader = Trickle {
+            text: text.as_bytes(),
+            step: chunk_size,
+        };
+        regex
+            .search_stream(reader)
+            .chunk_size(chunk_size)
+            .look_behind(4)
+            .max_match_len(16)
+            .map(|caps| caps.unwrap().pos(0).unwrap())
+            .collect()
+    }
+
+    fn all_matches(pattern: &str, text: &str) -> Vec<(u64, u64)> {
+        Regex::new(pattern)
+            .unwrap()
+            .find_iter(text)
+            .map(|(s, e)| (s as u64, e as u64))
+            .collect()
+    }
+
+    #[test]
+    fn test_stream_matches_across_chunks() {
+        let text = "one two three four five six seven eight nine ten";
+        for pattern in &[r"\w+", r"\b\w", r"(?<=e )\w+", r"e\s*", r"x*", r"^\w+|\w+$"] {
+            for &chunk_size in &[1, 2, 3, 7, 64] {
+                assert_eq!(
+                    stream_matches(pattern, text, chunk_size),
+                    all_matches(pattern, text),
+                    "{} with chunks of {}",
+                    pattern,
+                    chunk_size
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn test_stream_multibyte_chunks() {
+        let text = "ééé aéb çà éé";
+        for &chunk_size in &[1, 2, 3, 5] {
+            assert_eq!(
+                stream_matches(r"\w+|", text, chunk_size),
+                all_matches(r"\w+|", text)
+            );
+        }
+    }
+
+    #[test]
+    fn test_stream_captures() {
+        let regex = Regex::new(r"(?<key>\w+)=(?<val>\d+)?").unwrap();
+        let caps: Vec<_> = regex
+            .search_stream(&b"a=1 bb= ccc=333"[..])
+            .chunk_size(2)
+            .collect::<io::Result<_>>()
+            .unwrap();
+        assert_eq!(caps.len(), 3);
+        assert_eq!(caps[0].name("key"), Some(&b"a"[..]));
+        assert_eq!(caps[1].name("val"), None);
+        assert_eq!(caps[2].pos(0), Some((8, 15)));
+        assert_eq!(caps[2].name_pos("val"), Some((12, 15)));
+        assert_eq!(caps[2].at(2), Some(&b"333"[..]));
+        assert_eq!(caps[2].len(), 3);
+    }
+
+    #[test]
+    fn test_stream_captures_duplicate_names() {
+        let caps: Vec<_> = {
+            let regex = Regex::new(r"(?<a>x)|(?<a>y)").unwrap();
+            regex
+                .search_stream(&b"xy"[..])
+                .collect::<io::Result<_>>()
+                .unwrap()
+        };
+        assert_eq!(caps[0].name("a"), Some(&b"x"[..]));
+        assert_eq!(caps[1].name("a"), Some(&b"y"[..]));
+        assert_eq!(caps[1].name_pos("a"), Some((1, 2)));
+    }
+
+    #[test]
+    fn test_stream_read_error() {
+        struct Failing;
+
+        impl io::Read for Failing {
+            fn read(&mut self, _: &mut [u8]) -> io::Result<usize> {
+                Err(io::Error::new(io::ErrorKind::Other, "broken"))
+            }
+        }
+
+        let regex = Regex::new("a").unwrap();
+        let mut searcher = regex.search_stream(Failing);
+        assert!(searcher.next().unwrap().is_err());
+        assert!(searcher.next().is_none());
+    }
+}
@@ -1,15 +1,97 @@
 #![allow(clippy::transmute_ptr_to_ptr)]
 #![allow(clippy::transmute_ptr_to_ref)]
 
-use super::{MetaCharType, RegexOptions, SyntaxBehavior, SyntaxOperator};
+use super::buffers::mbc_enc_len;
+use super::{
+    Encoding, Error, ErrorKind, MetaCharType, RegexOptions, SyntaxBehavior, SyntaxOperator,
+};
+use once_cell::sync::Lazy;
+use std::fmt;
+use std::hash::{Hash, Hasher};
 use std::mem::transmute;
 
+/// Operators which are Oniguruma or Perl extensions: `\K`, `\R`, `\N`,
+/// `\O`, `\X`, `\y`, `\Y`, conditionals, absent groups, `(?R)` style
+/// calls, callouts and `(?imxWDSPy)` options.
+const EXTENSIONS: SyntaxOperator = SyntaxOperator::SYNTAX_OPERATOR_ESC_CAPITAL_K_KEEP
+    .union(SyntaxOperator::SYNTAX_OPERATOR_ESC_CAPITAL_R_GENERAL_NEWLINE)
+    .union(SyntaxOperator::SYNTAX_OPERATOR_ESC_CAPITAL_N_O_SUPER_DOT)
+    .union(SyntaxOperator::SYNTAX_OPERATOR_ESC_X_Y_TEXT_SEGMENT)
+    .union(SyntaxOperator::SYNTAX_OPERATOR_QMARK_LPAREN_IF_ELSE)
+    .union(SyntaxOperator::SYNTAX_OPERATOR_QMARK_TILDE_ABSENT_GROUP)
+    .union(SyntaxOperator::SYNTAX_OPERATOR_QMARK_PERL_SUBEXP_CALL)
+    .union(SyntaxOperator::SYNTAX_OPERATOR_QMARK_BRACE_CALLOUT_CONTENTS)
+    .union(SyntaxOperator::SYNTAX_OPERATOR_ASTERISK_CALLOUT_NAME)
+    .union(SyntaxOperator::SYNTAX_OPERATOR_OPTION_ONIGURUMA);
+
+/// Escapes for the operators `Syntax::re2` disables. Oniguruma reads
+/// these as literal characters rather than rejecting them.
+const RE2_DISABLED_ESCAPES: [char; 10] = ['k', 'g', 'G', 'K', 'R', 'N', 'O', 'X', 'y', 'Y'];
+
+/// Groups `Syntax::re2` leaves out, which Oniguruma can only disable
+/// along with every other `(?...)` group.
+const RE2_DISABLED_GROUPS: [(&str, &str); 7] = [
+    ("?=", "lookahead"),
+    ("?!", "lookahead"),
+    ("?<=", "lookbehind"),
+    ("?<!", "lookbehind"),
+    ("?>", "atomic groups"),
+    ("?P=", "backreferences"),
+    ("?P>", "subexpression calls"),
+];
+
+/// A behaviour bit which Oniguruma 6.9.8 doesn't use, marking
+/// syntaxes copied from `Syntax::re2`. Patterns compiled with them are
+/// checked by `Syntax::check_pattern`. Keeping it in the syntax means
+/// that copies keep it too, whatever else is changed about them.
+const RE2_RESTRICTED: onig_sys::OnigSyntaxBehavior = 1 << 30;
+
+/// The meta character types, with their names.
+///
+/// `MetaCharType` values are indexes rather than bits, so its
+/// `iter_names` can't be used.
+const META_CHAR_NAMES: [(&str, MetaCharType); 6] = [
+    ("META_CHAR_ESCAPE", MetaCharType::META_CHAR_ESCAPE),
+    ("META_CHAR_ANYCHAR", MetaCharType::META_CHAR_ANYCHAR),
+    ("META_CHAR_ANYTIME", MetaCharType::META_CHAR_ANYTIME),
+    (
+        "META_CHAR_ZERO_OR_ONE_TIME",
+        MetaCharType::META_CHAR_ZERO_OR_ONE_TIME,
+    ),
+    (
+        "META_CHAR_ONE_OR_MORE_TIME",
+        MetaCharType::META_CHAR_ONE_OR_MORE_TIME,
+    ),
+    (
+        "META_CHAR_ANYCHAR_ANYTIME",
+        MetaCharType::META_CHAR_ANYCHAR_ANYTIME,
+    ),
+];
+
+/// Build a syntax preset by adjusting a copy of `base`.
+fn preset(
+    base: &Syntax,
+    enable: SyntaxOperator,
+    disable: SyntaxOperator,
+    enable_behavior: SyntaxBehavior,
+    disable_behavior: SyntaxBehavior,
+    options: RegexOptions,
+) -> Syntax {
+    let mut syntax = *base;
+    syntax.disable_operators(disable);
+    syntax.enable_operators(enable);
+    syntax.disable_behavior(disable_behavior);
+    syntax.enable_behavior(enable_behavior);
+    syntax.set_options(options);
+    syntax
+}
+
 /// Meta Character State
 ///
 /// Defines if a given meta character is enabled or not within a given
 /// syntax. If the character is enabled it also contains the rust
 /// `char` that it is set to.
-#[derive(Copy, Clone)]
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
 pub enum MetaChar {
     /// The meta character is set to the chosen `char`
     Character(char),
@@ -26,7 +108,7 @@ pub enum MetaChar {
 ///
 /// For a demonstration of creating a custom syntax see
 /// `examples/syntax.rs` in the main onig crate.
-#[derive(Debug, Clone, Copy)]
+#[derive(Clone, Copy)]
 #[repr(transparent)]
 pub struct Syntax {
     raw: onig_sys::OnigSyntaxType,
@@ -97,6 +179,168 @@ impl Syntax {
         unsafe { transmute(onig_sys::OnigDefaultSyntax) }
     }
 
+    /// ECMAScript syntax
+    ///
+    /// The syntax of JavaScript regular expressions and JSON Schema
+    /// `pattern` keywords, as closely as Oniguruma allows. Built from
+    /// the Java syntax with named groups, `\k<name>` and `\uHHHH`
+    /// escapes. `\w`, `\b` and `\d` only match ASCII, as in
+    /// ECMAScript.
+    ///
+    /// Differences from ECMAScript:
+    ///
+    ///  * Only the `i` and `x` flags can be set inline, with `(?i)`.
+    ///    Pass `RegexOptions` for the others.
+    ///  * Atomic groups `(?>...)` are accepted, as Oniguruma only allows
+    ///    them to be disabled along with all other `(?...)` groups.
+    ///  * `\u{...}`, `\p{Script=...}` and the `v` flag's set notation
+    ///    aren't supported.
+    ///  * `$` also matches before a newline at the end of the text.
+    ///    `REGEX_OPTION_MULTILINE` only lets `.` match a newline, and
+    ///    doesn't change this. For the `m` flag, where `$` matches
+    ///    before any newline, pass `REGEX_OPTION_NEGATE_SINGLELINE`.
+    ///  * Octal escapes from Annex B aren't supported.
+    pub fn ecmascript() -> &'static Syntax {
+        static ECMASCRIPT: Lazy<Syntax> = Lazy::new(|| {
+            preset(
+                Syntax::java(),
+                SyntaxOperator::SYNTAX_OPERATOR_QMARK_LT_NAMED_GROUP
+                    | SyntaxOperator::SYNTAX_OPERATOR_ESC_K_NAMED_BACKREF,
+                SyntaxOperator::SYNTAX_OPERATOR_ESC_AZ_BUF_ANCHOR
+                    | SyntaxOperator::SYNTAX_OPERATOR_ESC_CAPITAL_G_BEGIN_ANCHOR
+                    | SyntaxOperator::SYNTAX_OPERATOR_POSIX_BRACKET
+                    | SyntaxOperator::SYNTAX_OPERATOR_ESC_OCTAL3
+                    | SyntaxOperator::SYNTAX_OPERATOR_ESC_CAPITAL_Q_QUOTE
+                    | SyntaxOperator::SYNTAX_OPERATOR_OPTION_PERL
+                    | SyntaxOperator::SYNTAX_OPERATOR_PLUS_POSSESSIVE_REPEAT
+                    | SyntaxOperator::SYNTAX_OPERATOR_PLUS_POSSESSIVE_INTERVAL
+                    | SyntaxOperator::SYNTAX_OPERATOR_CCLASS_SET_OP
+                    | EXTENSIONS,
+                SyntaxBehavior::empty(),
+                SyntaxBehavior::empty(),
+                RegexOptions::REGEX_OPTION_SINGLELINE
+                    | RegexOptions::REGEX_OPTION_WORD_IS_ASCII
+                    | RegexOptions::REGEX_OPTION_DIGIT_IS_ASCII,
+            )
+        });
+        &ECMASCRIPT
+    }
+
+    /// PCRE2 like syntax
+    ///
+    /// Perl syntax with the PCRE2 extensions Oniguruma also supports:
+    /// named groups, `(?P<name>...)`, `\K`, `\R`, `\X`, conditionals,
+    /// and recursion with `(?R)` and `\g<name>`. Unnamed groups
+    /// capture alongside named ones, and lookbehind may have
+    /// alternatives of different lengths.
+    ///
+    /// Differences from PCRE2:
+    ///
+    ///  * Backtracking verbs such as `(*SKIP)`, callouts such as `(?C1)`
+    ///    and numbered calls such as `(?1)` aren't supported.
+    ///  * `\g{n}` and `\g-n` backreferences aren't supported. `\h`
+    ///    and `\v` aren't classes of horizontal and vertical space.
+    ///  * Duplicate group names need no `(?J)`.
+    pub fn pcre2() -> &'static Syntax {
+        static PCRE2: Lazy<Syntax> = Lazy::new(|| {
+            preset(
+                Syntax::perl_ng(),
+                SyntaxOperator::SYNTAX_OPERATOR_QMARK_CAPITAL_P_NAME,
+                SyntaxOperator::SYNTAX_OPERATOR_QMARK_TILDE_ABSENT_GROUP
+                    | SyntaxOperator::SYNTAX_OPERATOR_QMARK_BRACE_CALLOUT_CONTENTS
+                    | SyntaxOperator::SYNTAX_OPERATOR_ASTERISK_CALLOUT_NAME,
+                SyntaxBehavior::SYNTAX_BEHAVIOR_DIFFERENT_LEN_ALT_LOOK_BEHIND
+                    | SyntaxBehavior::SYNTAX_BEHAVIOR_VARIABLE_LEN_LOOK_BEHIND,
+                SyntaxBehavior::SYNTAX_BEHAVIOR_CAPTURE_ONLY_NAMED_GROUP,
+                RegexOptions::REGEX_OPTION_SINGLELINE,
+            )
+        });
+        &PCRE2
+    }
+
+    /// .NET syntax
+    ///
+    /// The syntax of `System.Text.RegularExpressions`, as closely as
+    /// Oniguruma allows. Built from the Java syntax with named groups
+    /// written `(?<name>...)` or `(?'name'...)`, `\k<name>`
+    /// backreferences, conditionals and duplicate group names.
+    ///
+    /// Differences from .NET:
+    ///
+    ///  * Balancing groups `(?<a-b>...)`, character class subtraction
+    ///    `[a-z-[aeiou]]` and the `n` and `x` inline options aren't
+    ///    supported.
+    ///  * Named groups are numbered in the order they appear, rather
+    ///    than after all unnamed groups.
+    ///  * There's no right to left matching.
+    pub fn dotnet() -> &'static Syntax {
+        static DOTNET: Lazy<Syntax> = Lazy::new(|| {
+            preset(
+                Syntax::java(),
+                SyntaxOperator::SYNTAX_OPERATOR_QMARK_LT_NAMED_GROUP
+                    | SyntaxOperator::SYNTAX_OPERATOR_ESC_K_NAMED_BACKREF
+                    | SyntaxOperator::SYNTAX_OPERATOR_QMARK_LPAREN_IF_ELSE,
+                SyntaxOperator::SYNTAX_OPERATOR_POSIX_BRACKET
+                    | SyntaxOperator::SYNTAX_OPERATOR_ESC_CAPITAL_Q_QUOTE
+                    | SyntaxOperator::SYNTAX_OPERATOR_PLUS_POSSESSIVE_REPEAT
+                    | SyntaxOperator::SYNTAX_OPERATOR_PLUS_POSSESSIVE_INTERVAL
+                    | SyntaxOperator::SYNTAX_OPERATOR_CCLASS_SET_OP,
+                SyntaxBehavior::SYNTAX_BEHAVIOR_ALLOW_MULTIPLEX_DEFINITION_NAME,
+                SyntaxBehavior::empty(),
+                RegexOptions::REGEX_OPTION_SINGLELINE,
+            )
+        });
+        &DOTNET
+    }
+
+    /// RE2 style restricted syntax
+    ///
+    /// Perl syntax without the features RE2 leaves out because they
+    /// can't be matched in linear time: backreferences, subroutine
+    /// calls, lookaround, atomic groups, conditionals, possessive
+    /// repeats, `\G` and Oniguruma's extensions. Patterns which use
+    /// them, such as `(a)\1`, `\k<name>` or `(?<=a)b`, fail to compile.
+    /// Groups can be named with `(?P<name>...)` or `(?<name>...)`.
+    ///
+    /// Oniguruma reads some of these as literal or octal escapes, or
+    /// can only disable them along with every other `(?...)` group, so
+    /// patterns compiled with this syntax are also checked before
+    /// they're compiled. Copies of the syntax are checked too, even
+    /// once their operators, behaviour or options have been changed.
+    ///
+    /// Oniguruma is still a backtracking engine, so this doesn't give
+    /// RE2's linear time guarantee. When matching untrusted patterns,
+    /// also set a retry limit with `Limits` or `MatchParam`.
+    ///
+    /// Differences from RE2:
+    ///
+    ///  * `$` also matches before a newline at the end of the text,
+    ///    unless `(?m)` makes it match before any newline.
+    ///    `REGEX_OPTION_MULTILINE` only lets `.` match a newline, and
+    ///    doesn't change this.
+    pub fn re2() -> &'static Syntax {
+        static RE2: Lazy<Syntax> = Lazy::new(|| {
+            let mut syntax = preset(
+                Syntax::perl_ng(),
+                SyntaxOperator::SYNTAX_OPERATOR_QMARK_CAPITAL_P_NAME,
+                SyntaxOperator::SYNTAX_OPERATOR_DECIMAL_BACKREF
+                    | SyntaxOperator::SYNTAX_OPERATOR_ESC_K_NAMED_BACKREF
+                    | SyntaxOperator::SYNTAX_OPERATOR_ESC_G_SUBEXP_CALL
+                    | SyntaxOperator::SYNTAX_OPERATOR_ESC_CAPITAL_G_BEGIN_ANCHOR
+                    | SyntaxOperator::SYNTAX_OPERATOR_PLUS_POSSESSIVE_REPEAT
+                    | SyntaxOperator::SYNTAX_OPERATOR_PLUS_POSSESSIVE_INTERVAL
+                    | EXTENSIONS,
+                SyntaxBehavior::empty(),
+                SyntaxBehavior::SYNTAX_BEHAVIOR_CAPTURE_ONLY_NAMED_GROUP
+                    | SyntaxBehavior::SYNTAX_BEHAVIOR_ALLOW_MULTIPLEX_DEFINITION_NAME,
+                RegexOptions::REGEX_OPTION_SINGLELINE,
+            );
+            syntax.set_re2_restricted(true);
+            syntax
+        });
+        &RE2
+    }
+
     /// Retrieve the operators for this syntax
     pub fn operators(&self) -> SyntaxOperator {
         SyntaxOperator::from_bits_truncate(self.operators_bits())
@@ -146,14 +390,18 @@ impl Syntax {
 
     /// Retrieves the syntax behaviours
     pub fn behavior(&self) -> SyntaxBehavior {
-        SyntaxBehavior::from_bits_truncate(unsafe {
-            onig_sys::onig_get_syntax_behavior(self.raw_mut())
-        })
+        SyntaxBehavior::from_bits_truncate(self.behavior_bits())
+    }
+
+    /// Retrieve the raw behaviour bits
+    fn behavior_bits(&self) -> onig_sys::OnigSyntaxBehavior {
+        unsafe { onig_sys::onig_get_syntax_behavior(self.raw_mut()) }
     }
 
     /// Overwrite the syntax behaviour for this syntax.
     pub fn set_behavior(&mut self, behavior: SyntaxBehavior) {
-        let behavior = behavior.bits() as onig_sys::OnigSyntaxBehavior;
+        let behavior =
+            (behavior.bits() & !RE2_RESTRICTED) | (self.behavior_bits() & RE2_RESTRICTED);
         unsafe {
             onig_sys::onig_set_syntax_behavior(&mut self.raw, behavior);
         }
@@ -161,14 +409,37 @@ impl Syntax {
 
     /// Enable a given behaviour for this syntax
     pub fn enable_behavior(&mut self, behavior: SyntaxBehavior) {
-        let behavior = self.behavior() | behavior;
-        self.set_behavior(behavior)
+        let behavior = self.behavior_bits() | (behavior.bits() & !RE2_RESTRICTED);
+        unsafe {
+            onig_sys::onig_set_syntax_behavior(&mut self.raw, behavior);
+        }
     }
 
     /// Disable a given behaviour for this syntax
     pub fn disable_behavior(&mut self, behavior: SyntaxBehavior) {
-        let behavior = self.behavior() & !behavior;
-        self.set_behavior(behavior)
+        let behavior = self.behavior_bits() & !(behavior.bits() & !RE2_RESTRICTED);
+        unsafe {
+            onig_sys::onig_set_syntax_behavior(&mut self.raw, behavior);
+        }
+    }
+
+    /// Are patterns compiled with this syntax held to `Syntax::re2`'s
+    /// restrictions?
+    fn is_re2_restricted(&self) -> bool {
+        self.behavior_bits() & RE2_RESTRICTED != 0
+    }
+
+    /// Mark this syntax as holding patterns to `Syntax::re2`'s
+    /// restrictions, or not.
+    fn set_re2_restricted(&mut self, restricted: bool) {
+        let behavior = if restricted {
+            self.behavior_bits() | RE2_RESTRICTED
+        } else {
+            self.behavior_bits() & !RE2_RESTRICTED
+        };
+        unsafe {
+            onig_sys::onig_set_syntax_behavior(&mut self.raw, behavior);
+        }
     }
 
     /// Retireve the syntax options for this syntax
@@ -202,14 +473,452 @@ impl Syntax {
         }
     }
 
+    /// Get a given meta character's state
+    ///
+    /// Returns `MetaChar::Ineffective` if `what` isn't one of the
+    /// `MetaCharType` constants.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use onig::{MetaChar, MetaCharType, Syntax};
+    ///
+    /// let ruby = Syntax::ruby();
+    /// assert_eq!(
+    ///     ruby.meta_char(MetaCharType::META_CHAR_ESCAPE),
+    ///     MetaChar::Character('\\')
+    /// );
+    /// assert_eq!(
+    ///     ruby.meta_char(MetaCharType::META_CHAR_ANYCHAR),
+    ///     MetaChar::Ineffective
+    /// );
+    /// ```
+    pub fn meta_char(&self, what: MetaCharType) -> MetaChar {
+        let table = &self.raw.meta_char_table;
+        let code = match what {
+            MetaCharType::META_CHAR_ESCAPE => table.esc,
+            MetaCharType::META_CHAR_ANYCHAR => table.anychar,
+            MetaCharType::META_CHAR_ANYTIME => table.anytime,
+            MetaCharType::META_CHAR_ZERO_OR_ONE_TIME => table.zero_or_one_time,
+            MetaCharType::META_CHAR_ONE_OR_MORE_TIME => table.one_or_more_time,
+            MetaCharType::META_CHAR_ANYCHAR_ANYTIME => table.anychar_anytime,
+            _ => onig_sys::ONIG_INEFFECTIVE_META_CHAR,
+        };
+        if code == onig_sys::ONIG_INEFFECTIVE_META_CHAR {
+            return MetaChar::Ineffective;
+        }
+        char::from_u32(code).map_or(MetaChar::Ineffective, MetaChar::Character)
+    }
+
+    /// Escape Text for this Syntax
+    ///
+    /// Returns a pattern which matches `text` literally when compiled
+    /// with this syntax. Characters which are operators or variable
+    /// meta characters in this syntax are prefixed with its escape
+    /// character. `#` and whitespace are escaped too, so the pattern
+    /// still matches literally with `REGEX_OPTION_EXTEND`, unless the
+    /// syntax has no escape character.
+    ///
+    /// Some characters are operators both with and without the escape
+    /// character, such as `+` in a syntax with both `+` and `\+`. These
+    /// are quoted with `\Q...\E` if the syntax supports it, and put in
+    /// a bracket expression otherwise.
+    ///
+    /// # Arguments
+    ///
+    ///  * `text` - The text to match literally
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use onig::{Regex, RegexOptions, Syntax};
+    ///
+    /// assert_eq!(Syntax::perl().escape("1+1=2?"), r"1\+1=2\?");
+    /// assert_eq!(Syntax::grep().escape("1+1=2?"), "1+1=2?");
+    ///
+    /// let syntax = Syntax::posix_basic();
+    /// let pattern = syntax.escape("a.(b)*");
+    /// let regex = Regex::with_options(&pattern, RegexOptions::REGEX_OPTION_NONE, syntax).unwrap();
+    /// assert_eq!(regex.find("xa.(b)*"), Some((1, 7)));
+    /// ```
+    pub fn escape(&self, text: &str) -> String {
+        let operators = self.operators();
+        let esc = match self.meta_char(MetaCharType::META_CHAR_ESCAPE) {
+            MetaChar::Character(esc)
+                if !operators.contains(SyntaxOperator::SYNTAX_OPERATOR_INEFFECTIVE_ESCAPE) =>
+            {
+                Some(esc)
+            }
+            _ => None,
+        };
+
+        let mut escaped = String::with_capacity(text.len());
+        for c in text.chars() {
+            if !self.is_special(c, esc) {
+                escaped.push(c);
+            } else if let Some(esc) = esc.filter(|_| !self.is_escaped_operator(c)) {
+                escaped.push(esc);
+                escaped.push(c);
+            } else if let Some(esc) = esc
+                .filter(|_| operators.contains(SyntaxOperator::SYNTAX_OPERATOR_ESC_CAPITAL_Q_QUOTE))
+            {
+                escaped.extend([esc, 'Q', c, esc, 'E']);
+            } else if operators.contains(SyntaxOperator::SYNTAX_OPERATOR_BRACKET_CC) {
+                escaped.extend(['[', c, ']']);
+            } else {
+                escaped.push(c);
+            }
+        }
+        escaped
+    }
+
+    /// Check a pattern before compiling it with this syntax
+    ///
+    /// Oniguruma can't reject everything `Syntax::re2` leaves out, so
+    /// patterns compiled with it, or a copy of it, are checked here
+    /// first for backreferences, subexpression calls, lookaround,
+    /// atomic groups and escapes of the other operators it disables.
+    /// Other syntaxes are left to Oniguruma.
+    pub(crate) fn check_pattern(&self, pattern: &[u8], enc: Encoding) -> Result<(), Error> {
+        if !self.is_re2_restricted() {
+            return Ok(());
+        }
+        let esc = match self.meta_char(MetaCharType::META_CHAR_ESCAPE) {
+            MetaChar::Character(esc) => esc,
+            MetaChar::Ineffective => '\\',
+        };
+
+        // Decode the pattern, keeping the byte offset of each character.
+        // A truncated character at the end is left for Oniguruma to
+        // reject.
+        let raw = unsafe { &*enc.as_raw() };
+        let mut chars = Vec::with_capacity(pattern.len());
+        let mut pos = 0;
+        while pos < pattern.len() {
+            let rest = &pattern[pos..];
+            let len = mbc_enc_len(enc, rest);
+            if len > rest.len() {
+                break;
+            }
+            let code = raw
+                .mbc_to_code
+                .map_or(u32::from(rest[0]), |to_code| unsafe {
+                    to_code(rest.as_ptr(), rest[len..].as_ptr())
+                });
+            chars.push((pos, char::from_u32(code).unwrap_or('\0')));
+            pos += len;
+        }
+        let at = |i: usize| chars.get(i).map(|&(_, c)| c);
+        let reject = |start: usize, end: usize, what: &str| {
+            let mut error = Error::custom(
+                ErrorKind::Syntax,
+                format!("{} aren't supported by the RE2 syntax", what),
+            );
+            error.pattern = Some(pattern.to_vec());
+            error.span = Some(chars[start].0..chars.get(end).map_or(pattern.len(), |&(p, _)| p));
+            Err(error)
+        };
+
+        let mut class_depth = 0;
+        let mut i = 0;
+        while i < chars.len() {
+            match chars[i].1 {
+                c if c == esc => match at(i + 1) {
+                    Some('Q') => {
+                        i += 2;
+                        while i < chars.len() && !(at(i) == Some(esc) && at(i + 1) == Some('E')) {
+                            i += 1;
+                        }
+                    }
+                    // As in RE2, `\1` to `\7` start an octal escape when
+                    // another octal digit follows, and are otherwise
+                    // backreferences.
+                    Some(d @ '1'..='9')
+                        if d > '7' || !at(i + 2).is_some_and(|c| ('0'..='7').contains(&c)) =>
+                    {
+                        return reject(i, i + 2, "backreferences");
+                    }
+                    Some(c) if RE2_DISABLED_ESCAPES.contains(&c) => {
+                        let what = match c {
+                            'k' => "backreferences",
+                            'g' => "subexpression calls",
+                            _ => "escapes for Oniguruma extensions",
+                        };
+                        return reject(i, i + 2, what);
+                    }
+                    _ => i += 1,
+                },
+                '[' => {
+                    class_depth += 1;
+                    // A `]` straight after the opening bracket is literal.
+                    if at(i + 1) == Some('^') {
+                        i += 1;
+                    }
+                    if at(i + 1) == Some(']') {
+                        i += 1;
+                    }
+                }
+                ']' if class_depth > 0 => class_depth -= 1,
+                '(' if class_depth == 0 => {
+                    let group = |prefix: &str| {
+                        prefix
+                            .chars()
+                            .enumerate()
+                            .all(|(n, c)| at(i + 1 + n) == Some(c))
+                    };
+                    if let Some(&(prefix, what)) = RE2_DISABLED_GROUPS
+                        .iter()
+                        .find(|&&(prefix, _)| group(prefix))
+                    {
+                        return reject(i, i + 1 + prefix.len(), what);
+                    }
+                }
+                _ => {}
+            }
+            i += 1;
+        }
+        Ok(())
+    }
+
+    /// Does `c` need escaping to be matched literally?
+    fn is_special(&self, c: char, esc: Option<char>) -> bool {
+        let operator = match c {
+            '.' => SyntaxOperator::SYNTAX_OPERATOR_DOT_ANYCHAR,
+            '*' => SyntaxOperator::SYNTAX_OPERATOR_ASTERISK_ZERO_INF,
+            '+' => SyntaxOperator::SYNTAX_OPERATOR_PLUS_ONE_INF,
+            '?' => SyntaxOperator::SYNTAX_OPERATOR_QMARK_ZERO_ONE,
+            '{' | '}' => SyntaxOperator::SYNTAX_OPERATOR_BRACE_INTERVAL,
+            '|' => SyntaxOperator::SYNTAX_OPERATOR_VBAR_ALT,
+            '(' | ')' => SyntaxOperator::SYNTAX_OPERATOR_LPAREN_SUBEXP,
+            '[' | ']' => SyntaxOperator::SYNTAX_OPERATOR_BRACKET_CC,
+            '^' | '$' => SyntaxOperator::SYNTAX_OPERATOR_LINE_ANCHOR,
+            _ => SyntaxOperator::empty(),
+        };
+        let operators = self.operators();
+        operators.intersects(operator)
+            || (esc.is_some() && (Some(c) == esc || c == '#' || c.is_whitespace()))
+            || (operators.contains(SyntaxOperator::SYNTAX_OPERATOR_VARIABLE_META_CHARACTERS)
+                // Skip the escape character, which was checked above.
+                && META_CHAR_NAMES[1..]
+                    .iter()
+                    .any(|&(_, what)| self.meta_char(what) == MetaChar::Character(c)))
+    }
+
+    /// Is the escape character followed by `c` an operator?
+    fn is_escaped_operator(&self, c: char) -> bool {
+        let operator = match c {
+            '*' => SyntaxOperator::SYNTAX_OPERATOR_ESC_ASTERISK_ZERO_INF,
+            '+' => SyntaxOperator::SYNTAX_OPERATOR_ESC_PLUS_ONE_INF,
+            '?' => SyntaxOperator::SYNTAX_OPERATOR_ESC_QMARK_ZERO_ONE,
+            '{' | '}' => SyntaxOperator::SYNTAX_OPERATOR_ESC_BRACE_INTERVAL,
+            '|' => SyntaxOperator::SYNTAX_OPERATOR_ESC_VBAR_ALT,
+            '(' | ')' => SyntaxOperator::SYNTAX_OPERATOR_ESC_LPAREN_SUBEXP,
+            '<' | '>' => SyntaxOperator::SYNTAX_OPERATOR_ESC_LTGT_WORD_BEGIN_END,
+            '`' | '\'' => SyntaxOperator::SYNTAX_OPERATOR_ESC_GNU_BUF_ANCHOR,
+            _ => return c.is_ascii_alphanumeric(),
+        };
+        self.operators().intersects(operator)
+    }
+
     fn raw_mut(&self) -> *mut onig_sys::OnigSyntaxType {
         &self.raw as *const onig_sys::OnigSyntaxType as *mut onig_sys::OnigSyntaxType
     }
 }
 
+impl fmt::Debug for Syntax {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("Syntax")
+            .field("operators", &self.operators())
+            .field("behavior", &self.behavior())
+            .field("options", &self.options())
+            .field("meta_chars", &MetaChars(self))
+            .field("re2_restricted", &self.is_re2_restricted())
+            .finish()
+    }
+}
+
+/// Debug output for the meta characters of a `Syntax`.
+struct MetaChars<'a>(&'a Syntax);
+
+impl fmt::Debug for MetaChars<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_map()
+            .entries(
+                META_CHAR_NAMES
+                    .iter()
+                    .map(|&(name, what)| (name, self.0.meta_char(what))),
+            )
+            .finish()
+    }
+}
+
+impl PartialEq for Syntax {
+    fn eq(&self, other: &Syntax) -> bool {
+        self.raw == other.raw
+    }
+}
+
+impl Eq for Syntax {}
+
+impl Hash for Syntax {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        let meta = &self.raw.meta_char_table;
+        (
+            self.raw.op,
+            self.raw.op2,
+            self.raw.behavior,
+            self.raw.options,
+        )
+            .hash(state);
+        (
+            meta.esc,
+            meta.anychar,
+            meta.anytime,
+            meta.zero_or_one_time,
+            meta.one_or_more_time,
+            meta.anychar_anytime,
+        )
+            .hash(state);
+    }
+}
+
+/// The serialized form of a `Syntax`
+///
+/// Operators, behaviours and options are lists of flag names, such as
+/// `SYNTAX_OPERATOR_DOT_ANYCHAR`. Meta characters are `None` when
+/// ineffective. `re2_restricted` is only written for copies of
+/// `Syntax::re2`. Everything but the operators can be left out, giving
+/// no behaviours or options and `\` as the only meta character.
+#[cfg(feature = "serde")]
+#[derive(serde::Serialize, serde::Deserialize)]
+#[serde(rename = "Syntax", deny_unknown_fields)]
+struct SyntaxDef {
+    operators: Vec<String>,
+    #[serde(default)]
+    behavior: Vec<String>,
+    #[serde(default)]
+    options: Vec<String>,
+    #[serde(default)]
+    meta_chars: MetaCharsDef,
+    #[serde(default, skip_serializing_if = "is_false")]
+    re2_restricted: bool,
+}
+
+#[cfg(feature = "serde")]
+fn is_false(b: &bool) -> bool {
+    !b
+}
+
+/// The serialized form of a `Syntax`'s meta characters.
+#[cfg(feature = "serde")]
+#[derive(serde::Serialize, serde::Deserialize)]
+#[serde(default, deny_unknown_fields)]
+struct MetaCharsDef {
+    escape: Option<char>,
+    anychar: Option<char>,
+    anytime: Option<char>,
+    zero_or_one_time: Option<char>,
+    one_or_more_time: Option<char>,
+    anychar_anytime: Option<char>,
+}
+
+#[cfg(feature = "serde")]
+impl Default for MetaCharsDef {
+    fn default() -> Self {
+        MetaCharsDef {
+            escape: Some('\\'),
+            anychar: None,
+            anytime: None,
+            zero_or_one_time: None,
+            one_or_more_time: None,
+            anychar_anytime: None,
+        }
+    }
+}
+
+#[cfg(feature = "serde")]
+fn flag_names<T: bitflags::Flags>(flags: T) -> Vec<String> {
+    flags
+        .iter_names()
+        .map(|(name, _)| name.to_string())
+        .collect()
+}
+
+#[cfg(feature = "serde")]
+fn flags_from_names<T, E>(names: &[String]) -> Result<T, E>
+where
+    T: bitflags::Flags,
+    E: serde::de::Error,
+{
+    names.iter().try_fold(T::empty(), |flags, name| {
+        T::from_name(name)
+            .map(|flag| flags.union(flag))
+            .ok_or_else(|| E::custom(format!("unknown flag `{}`", name)))
+    })
+}
+
+#[cfg(feature = "serde")]
+impl serde::Serialize for Syntax {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let meta_char = |what| match self.meta_char(what) {
+            MetaChar::Character(c) => Some(c),
+            MetaChar::Ineffective => None,
+        };
+        SyntaxDef {
+            operators: flag_names(self.operators()),
+            behavior: flag_names(self.behavior()),
+            options: flag_names(self.options()),
+            meta_chars: MetaCharsDef {
+                escape: meta_char(MetaCharType::META_CHAR_ESCAPE),
+                anychar: meta_char(MetaCharType::META_CHAR_ANYCHAR),
+                anytime: meta_char(MetaCharType::META_CHAR_ANYTIME),
+                zero_or_one_time: meta_char(MetaCharType::META_CHAR_ZERO_OR_ONE_TIME),
+                one_or_more_time: meta_char(MetaCharType::META_CHAR_ONE_OR_MORE_TIME),
+                anychar_anytime: meta_char(MetaCharType::META_CHAR_ANYCHAR_ANYTIME),
+            },
+            re2_restricted: self.is_re2_restricted(),
+        }
+        .serialize(serializer)
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<'de> serde::Deserialize<'de> for Syntax {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Syntax, D::Error> {
+        let def = SyntaxDef::deserialize(deserializer)?;
+        let mut syntax = *Syntax::asis();
+        syntax.set_operators(flags_from_names(&def.operators)?);
+        syntax.set_behavior(flags_from_names(&def.behavior)?);
+        syntax.set_options(flags_from_names(&def.options)?);
+        syntax.set_re2_restricted(def.re2_restricted);
+        let meta = def.meta_chars;
+        for (what, c) in [
+            (MetaCharType::META_CHAR_ESCAPE, meta.escape),
+            (MetaCharType::META_CHAR_ANYCHAR, meta.anychar),
+            (MetaCharType::META_CHAR_ANYTIME, meta.anytime),
+            (
+                MetaCharType::META_CHAR_ZERO_OR_ONE_TIME,
+                meta.zero_or_one_time,
+            ),
+            (
+                MetaCharType::META_CHAR_ONE_OR_MORE_TIME,
+                meta.one_or_more_time,
+            ),
+            (
+                MetaCharType::META_CHAR_ANYCHAR_ANYTIME,
+                meta.anychar_anytime,
+            ),
+        ] {
+            syntax.set_meta_char(what, c.map_or(MetaChar::Ineffective, MetaChar::Character));
+        }
+        Ok(syntax)
+    }
+}
+
 #[cfg(test)]
 mod test {
     use super::*;
+    use crate::Regex;
 
     #[test]
     fn round_trip_bits() {
@@ -219,4 +928,298 @@ mod test {
         syn.disable_operators(SyntaxOperator::SYNTAX_OPERATOR_ESC_X_BRACE_HEX8);
         assert_eq!(Syntax::python().raw, syn.raw);
     }
+
+    #[test]
+    fn syntax_presets() {
+        let compiles = |syntax: &Syntax, pattern: &str| {
+            Regex::with_options(pattern, RegexOptions::REGEX_OPTION_NONE, syntax).is_ok()
+        };
+
+        let ecmascript = Syntax::ecmascript();
+        assert!(compiles(ecmascript, r"(?<year>\d{4})-\k<year>(?<=\d)"));
+        assert!(!compiles(ecmascript, "(?P<year>a)"));
+        assert!(!compiles(ecmascript, "(?s)a"));
+        assert!(!compiles(ecmascript, "(?<a>x)(?<a>y)"));
+        assert!(!compiles(ecmascript, "(a)(?(1)b|c)"));
+        let word =
+            Regex::with_options(r"\w+", RegexOptions::REGEX_OPTION_NONE, ecmascript).unwrap();
+        assert_eq!(word.find("été"), Some((2, 3)));
+        let escape =
+            Regex::with_options(r"\u00e9\A", RegexOptions::REGEX_OPTION_NONE, ecmascript).unwrap();
+        assert!(escape.is_match("éA"));
+
+        let pcre2 = Syntax::pcre2();
+        assert!(compiles(pcre2, r"(?P<a>x)(?(1)y|z)(?<=ab|c)\K\R"));
+        assert!(!compiles(pcre2, "(?~a)"));
+        assert!(!compiles(pcre2, "(?{foo})"));
+        let regex =
+            Regex::with_options("(a)(?<b>b)", RegexOptions::REGEX_OPTION_NONE, pcre2).unwrap();
+        assert_eq!(regex.captures_len(), 2);
+
+        let dotnet = Syntax::dotnet();
+        assert!(compiles(
+            dotnet,
+            r"(?'a'x)(?<a>y)\k<a>(?(a)b|c)(?<=a+)\u0041"
+        ));
+        assert!(!compiles(dotnet, "(?P<a>x)"));
+        assert!(!compiles(dotnet, "(?R)"));
+        let regex = Regex::with_options("a++", RegexOptions::REGEX_OPTION_NONE, dotnet).unwrap();
+        assert!(regex.is_match("aa"));
+
+        let re2 = Syntax::re2();
+        assert!(compiles(re2, r"(?P<a>x)(?<b>y)(?:z)\p{^Greek}"));
+        assert!(!compiles(re2, "(a)(?(1)b|c)"));
+        assert!(!compiles(re2, "(?R)"));
+        assert!(!compiles(re2, "(?<a>x)(?<a>y)"));
+        assert!(!compiles(re2, "(?<=ab|c)x"));
+        assert!(!compiles(re2, r"(a)\1"));
+        assert!(!compiles(re2, r"(?<a>x)\k<a>"));
+        assert!(!compiles(re2, r"(?<a>x)\g<a>"));
+        assert!(!compiles(re2, r"(?<a>x)(?P=a)"));
+        assert!(!compiles(re2, r"(?<=a)x"));
+        assert!(!compiles(re2, r"x(?!a)"));
+        assert!(!compiles(re2, r"(?>a)"));
+        assert!(!compiles(re2, r"[\8]"));
+        assert!(!compiles(re2, r"a\K"));
+        assert!(compiles(re2, r"\12\0[(?=][]a]\\k\Q\1(?=\E"));
+        let error =
+            Regex::with_options(r"(a)+\1", RegexOptions::REGEX_OPTION_NONE, re2).unwrap_err();
+        assert_eq!(error.kind(), ErrorKind::Syntax);
+        assert_eq!(error.diagnostic().unwrap(), "(a)+\\1\n    ^^");
+    }
+
+    #[test]
+    fn syntax_re2_copies_stay_restricted() {
+        let compiles = |syntax: &Syntax, pattern: &str| {
+            Regex::with_options(pattern, RegexOptions::REGEX_OPTION_NONE, syntax).is_ok()
+        };
+
+        let mut ignorecase = *Syntax::re2();
+        ignorecase.set_options(ignorecase.options() | RegexOptions::REGEX_OPTION_IGNORECASE);
+        let mut backrefs = *Syntax::re2();
+        backrefs.enable_operators(
+            SyntaxOperator::SYNTAX_OPERATOR_DECIMAL_BACKREF
+                | SyntaxOperator::SYNTAX_OPERATOR_ESC_K_NAMED_BACKREF
+                | SyntaxOperator::SYNTAX_OPERATOR_ESC_G_SUBEXP_CALL,
+        );
+        backrefs.set_behavior(SyntaxBehavior::SYNTAX_BEHAVIOR_ALLOW_MULTIPLEX_DEFINITION_NAME);
+        let mut percent = *Syntax::re2();
+        percent.set_meta_char(MetaCharType::META_CHAR_ESCAPE, MetaChar::Character('%'));
+
+        for syntax in [&ignorecase, &backrefs] {
+            assert!(compiles(syntax, r"(?<a>x)\p{Greek}"));
+            assert!(!compiles(syntax, r"(a)\1"));
+            assert!(!compiles(syntax, r"(a)\8"));
+            assert!(!compiles(syntax, r"(?<=a)b"));
+            assert!(!compiles(syntax, r"a\Kb"));
+            assert!(!compiles(syntax, r"(?<a>x)\g<a>"));
+        }
+        assert!(!compiles(&percent, "(a)%1"));
+        assert!(compiles(&percent, r"(a)\1"));
+        assert!(compiles(Syntax::perl_ng(), r"(a)\1(?<=a)b"));
+    }
+
+    #[test]
+    fn syntax_presets_dollar() {
+        for syntax in [Syntax::ecmascript(), Syntax::re2()] {
+            for options in [
+                RegexOptions::REGEX_OPTION_NONE,
+                RegexOptions::REGEX_OPTION_MULTILINE,
+            ] {
+                let regex = Regex::with_options("a$", options, syntax).unwrap();
+                assert_eq!(regex.find("a\n"), Some((0, 1)));
+                assert_eq!(regex.find("a\nb"), None);
+                assert!(!regex.is_match("a\n"));
+            }
+            let regex =
+                Regex::with_options("a$", RegexOptions::REGEX_OPTION_NEGATE_SINGLELINE, syntax)
+                    .unwrap();
+            assert_eq!(regex.find("a\nb"), Some((0, 1)));
+        }
+        let regex =
+            Regex::with_options("(?m)a$", RegexOptions::REGEX_OPTION_NONE, Syntax::re2()).unwrap();
+        assert_eq!(regex.find("a\nb"), Some((0, 1)));
+    }
+
+    #[test]
+    fn syntax_behavior_keeps_unnamed_bits() {
+        let mut syn = *Syntax::perl_ng();
+        syn.disable_behavior(SyntaxBehavior::SYNTAX_BEHAVIOR_CAPTURE_ONLY_NAMED_GROUP);
+        syn.enable_behavior(SyntaxBehavior::SYNTAX_BEHAVIOR_CAPTURE_ONLY_NAMED_GROUP);
+        assert_eq!(&syn, Syntax::perl_ng());
+    }
+
+    #[test]
+    fn syntax_equality() {
+        let mut syn = *Syntax::ruby();
+        assert_eq!(&syn, Syntax::ruby());
+        assert_ne!(Syntax::ruby(), Syntax::perl());
+        syn.set_meta_char(MetaCharType::META_CHAR_ESCAPE, MetaChar::Character('%'));
+        assert_ne!(&syn, Syntax::ruby());
+    }
+
+    fn all_syntaxes() -> Vec<&'static Syntax> {
+        vec![
+            Syntax::asis(),
+            Syntax::posix_basic(),
+            Syntax::posix_extended(),
+            Syntax::emacs(),
+            Syntax::grep(),
+            Syntax::gnu_regex(),
+            Syntax::java(),
+            Syntax::perl(),
+            Syntax::perl_ng(),
+            Syntax::python(),
+            Syntax::ruby(),
+            Syntax::oniguruma(),
+            Syntax::ecmascript(),
+            Syntax::pcre2(),
+            Syntax::dotnet(),
+            Syntax::re2(),
+        ]
+    }
+
+    #[test]
+    fn syntax_flags_are_all_named() {
+        for syntax in all_syntaxes() {
+            assert!(SyntaxOperator::from_bits(syntax.operators_bits()).is_some());
+            assert!(SyntaxBehavior::from_bits(syntax.behavior_bits() & !RE2_RESTRICTED).is_some());
+        }
+        assert!(Syntax::python()
+            .operators()
+            .contains(SyntaxOperator::SYNTAX_OPERATOR_QMARK_CAPITAL_P_NAME));
+        assert!(!Syntax::python()
+            .operators()
+            .contains(SyntaxOperator::SYNTAX_OPERATOR_ESC_O_BRACE_OCTAL));
+    }
+
+    #[test]
+    fn syntax_meta_char() {
+        let mut syn = *Syntax::ruby();
+        assert_eq!(
+            syn.meta_char(MetaCharType::META_CHAR_ESCAPE),
+            MetaChar::Character('\\')
+        );
+        assert_eq!(
+            syn.meta_char(MetaCharType::META_CHAR_ANYTIME),
+            MetaChar::Ineffective
+        );
+        syn.set_meta_char(MetaCharType::META_CHAR_ANYTIME, MetaChar::Character('%'));
+        assert_eq!(
+            syn.meta_char(MetaCharType::META_CHAR_ANYTIME),
+            MetaChar::Character('%')
+        );
+        assert_eq!(
+            syn.meta_char(MetaCharType::from_bits_retain(6)),
+            MetaChar::Ineffective
+        );
+    }
+
+    #[test]
+    fn syntax_debug() {
+        let debug = format!("{:?}", Syntax::perl());
+        assert!(debug.starts_with("Syntax { operators: SyntaxOperator(SYNTAX_OPERATOR_DOT_ANYCHAR"));
+        assert!(debug.contains("options: RegexOptions(REGEX_OPTION_SINGLELINE)"));
+        assert!(debug.contains(r#""META_CHAR_ESCAPE": Character('\\')"#));
+        assert!(debug.contains(r#""META_CHAR_ANYCHAR": Ineffective"#));
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn syntax_serde_round_trip() {
+        for syntax in all_syntaxes() {
+            let json = serde_json::to_string(syntax).unwrap();
+            let back: Syntax = serde_json::from_str(&json).unwrap();
+            assert_eq!(&back, syntax, "{}", json);
+        }
+
+        let json = serde_json::to_value(Syntax::perl()).unwrap();
+        assert_eq!(
+            json["options"],
+            serde_json::json!(["REGEX_OPTION_SINGLELINE"])
+        );
+        assert_eq!(json["meta_chars"]["escape"], "\\");
+        assert!(json["meta_chars"]["anychar"].is_null());
+        assert!(json.get("re2_restricted").is_none());
+        let json = serde_json::to_value(Syntax::re2()).unwrap();
+        assert_eq!(json["re2_restricted"], true);
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn syntax_deserialize_dialect() {
+        let syntax: Syntax = serde_json::from_str(
+            r#"{
+                "operators": [
+                    "SYNTAX_OPERATOR_DOT_ANYCHAR",
+                    "SYNTAX_OPERATOR_VARIABLE_META_CHARACTERS"
+                ],
+                "options": ["REGEX_OPTION_IGNORECASE"],
+                "meta_chars": { "escape": "%", "anytime": "~" }
+            }"#,
+        )
+        .unwrap();
+        assert_eq!(
+            syntax.meta_char(MetaCharType::META_CHAR_ANYTIME),
+            MetaChar::Character('~')
+        );
+        let regex = Regex::with_options("a.~%~", RegexOptions::REGEX_OPTION_NONE, &syntax).unwrap();
+        assert_eq!(regex.find("xAbc~"), Some((1, 5)));
+
+        let err =
+            serde_json::from_str::<Syntax>(r#"{ "operators": ["DOT_ANYCHAR"] }"#).unwrap_err();
+        assert!(err.to_string().contains("unknown flag `DOT_ANYCHAR`"));
+    }
+
+    #[test]
+    fn syntax_escape_matches_literally() {
+        let mut sql = *Syntax::perl();
+        sql.enable_operators(SyntaxOperator::SYNTAX_OPERATOR_VARIABLE_META_CHARACTERS);
+        sql.disable_operators(SyntaxOperator::SYNTAX_OPERATOR_DOT_ANYCHAR);
+        sql.set_meta_char(MetaCharType::META_CHAR_ESCAPE, MetaChar::Character('!'));
+        sql.set_meta_char(MetaCharType::META_CHAR_ANYCHAR, MetaChar::Character('_'));
+        sql.set_meta_char(
+            MetaCharType::META_CHAR_ANYCHAR_ANYTIME,
+            MetaChar::Character('%'),
+        );
+        let mut both = *Syntax::ruby();
+        both.enable_operators(
+            SyntaxOperator::SYNTAX_OPERATOR_ESC_PLUS_ONE_INF
+                | SyntaxOperator::SYNTAX_OPERATOR_ESC_VBAR_ALT,
+        );
+        let mut both_quote = both;
+        both_quote.enable_operators(SyntaxOperator::SYNTAX_OPERATOR_ESC_CAPITAL_Q_QUOTE);
+
+        let text = "a.b*c+d?e{1,2}f|g(h)i[j]k^l$m\\n/o<p>q`r's#t u\tv\nw\u{3000}x-y&z~é_%!";
+        let mut syntaxes = all_syntaxes();
+        syntaxes.extend([&sql, &both, &both_quote]);
+        for syntax in syntaxes {
+            for options in [
+                RegexOptions::REGEX_OPTION_NONE,
+                RegexOptions::REGEX_OPTION_EXTEND,
+            ] {
+                // Without an escape character whitespace can't be escaped.
+                if syntax == Syntax::asis() && options == RegexOptions::REGEX_OPTION_EXTEND {
+                    continue;
+                }
+                let pattern = syntax.escape(text);
+                let regex = Regex::with_options(&pattern, options, syntax)
+                    .unwrap_or_else(|e| panic!("{:?} in {:?}: {}", pattern, syntax, e));
+                assert_eq!(regex.find(text), Some((0, text.len())), "{:?}", pattern);
+                assert_eq!(regex.captures_len(), 0);
+
+                for c in text.chars().map(String::from) {
+                    let pattern = syntax.escape(&c);
+                    let regex = Regex::with_options(&pattern, options, syntax).unwrap();
+                    assert_eq!(regex.find(&c), Some((0, c.len())), "{:?}", pattern);
+                    assert!(!regex.is_match("Z"), "{:?}", pattern);
+                }
+            }
+        }
+
+        assert_eq!(sql.escape("_%!.a"), "!_!%!!.a");
+        assert_eq!(both.escape("a+|b"), "a[+][|]b");
+        assert_eq!(both_quote.escape("a+b"), r"a\Q+\Eb");
+        assert_eq!(Syntax::asis().escape(text), text);
+    }
 }
@@ -0,0 +1,262 @@
+//! Compile Warnings
+//!
+//! Oniguruma can warn about suspicious patterns while compiling them,
+//! for example an unescaped `]` in a pattern when the syntax
+//! has `SYNTAX_BEHAVIOR_WARN_CC_OP_NOT_ESCAPED` set. Warnings are
+//! passed to the handler set with `set_warn_handler`, or returned from
+//! `Regex::compile_with_warnings`. With the `log` feature enabled,
+//! warnings which aren't otherwise handled are sent to the `log`
+//! facade.
+
+use std::any::Any;
+use std::cell::RefCell;
+use std::ffi::CStr;
+use std::fmt;
+use std::os::raw::{c_char, c_int};
+use std::panic::{self, AssertUnwindSafe};
+use std::sync::{Arc, Mutex, Once};
+
+use once_cell::sync::Lazy;
+
+use super::{EncodedChars, Encoding, Error, Regex, RegexOptions, Syntax};
+
+/// A registered warning handler.
+type WarnHandler = Arc<dyn Fn(&Warning) + Send + Sync>;
+
+/// The handler set with `set_warn_handler`.
+static WARN_HANDLER: Lazy<Mutex<Option<WarnHandler>>> = Lazy::new(|| Mutex::new(None));
+
+thread_local! {
+    /// Warnings being collected by `Regex::compile_with_warnings` on
+    /// this thread.
+    static COLLECTED: RefCell<Option<Vec<Warning>>> = const { RefCell::new(None) };
+
+    /// A panic raised by the warning handler while compiling, to be
+    /// resumed once Oniguruma has returned.
+    static PANIC: RefCell<Option<Box<dyn Any + Send>>> = const { RefCell::new(None) };
+}
+
+/// A Compile Warning
+///
+/// A warning raised by Oniguruma while compiling a pattern.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Warning {
+    message: String,
+    verbose: bool,
+}
+
+impl Warning {
+    /// The warning message, including the pattern it was raised for.
+    pub fn message(&self) -> &str {
+        &self.message
+    }
+
+    /// Is this a verbose warning?
+    ///
+    /// Verbose warnings, such as a nested repeat being simplified,
+    /// come from Oniguruma's verbose warn function. They describe how
+    /// a pattern was interpreted rather than a likely mistake.
+    pub fn is_verbose(&self) -> bool {
+        self.verbose
+    }
+}
+
+impl fmt::Display for Warning {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(&self.message)
+    }
+}
+
+/// Set the Warning Handler
+///
+/// Registers `handler` to be called for every warning, verbose or
+/// not, raised while compiling a regex. Replaces any previously set
+/// handler. Warnings raised by `Regex::compile_with_warnings` are
+/// returned to its caller instead.
+///
+/// The handler is called while Oniguruma holds the lock used to
+/// compile regexes, so it mustn't compile a regex itself. A panic in
+/// the handler is resumed once the regex has been compiled.
+///
+/// # Examples
+///
+/// ```rust
+/// use onig::{set_warn_handler, Regex};
+///
+/// set_warn_handler(|warning| eprintln!("onig: {}", warning));
+/// Regex::new("[a]]").unwrap();
+/// ```
+pub fn set_warn_handler<F>(handler: F)
+where
+    F: Fn(&Warning) + Send + Sync + 'static,
+{
+    *WARN_HANDLER.lock().unwrap() = Some(Arc::new(handler));
+}
+
+/// Clear the Warning Handler
+///
+/// Removes the handler set with `set_warn_handler`. Warnings are then
+/// ignored, or sent to the `log` facade if the `log` feature is
+/// enabled.
+pub fn clear_warn_handler() {
+    *WARN_HANDLER.lock().unwrap() = None;
+}
+
+/// Route Oniguruma's warnings to Rust
+///
+/// Oniguruma only formats warnings if a warn function other than its
+/// default is set, so ours are set before the first regex is compiled.
+/// Oniguruma is explicitly initialised at the same time, as otherwise
+/// the first compile warns that it wasn't. Must be called with
+/// `REGEX_NEW_MUTEX` held.
+pub(crate) fn install() {
+    static INSTALL: Once = Once::new();
+    INSTALL.call_once(|| unsafe {
+        let mut encodings: Vec<_> = Encoding::ALL.iter().map(|enc| enc.as_raw()).collect();
+        onig_sys::onig_initialize(encodings.as_mut_ptr(), encodings.len() as c_int);
+        onig_sys::onig_set_warn_func(Some(warn));
+        onig_sys::onig_set_verb_warn_func(Some(verb_warn));
+    });
+}
+
+/// Resume a panic raised by the warning handler during a compile on
+/// this thread, if there was one.
+pub(crate) fn resume_panic() {
+    if let Some(payload) = PANIC.with(|p| p.borrow_mut().take()) {
+        panic::resume_unwind(payload);
+    }
+}
+
+unsafe extern "C" fn warn(message: *const c_char) {
+    dispatch(message, false);
+}
+
+unsafe extern "C" fn verb_warn(message: *const c_char) {
+    dispatch(message, true);
+}
+
+/// Pass a warning from Oniguruma on to the collector for this thread,
+/// the handler, or the `log` facade.
+unsafe fn dispatch(message: *const c_char, verbose: bool) {
+    if message.is_null() {
+        return;
+    }
+    let warning = Warning {
+        message: CStr::from_ptr(message).to_string_lossy().into_owned(),
+        verbose,
+    };
+    let warning = match COLLECTED.with(|c| match c.borrow_mut().as_mut() {
+        Some(collected) => {
+            collected.push(warning);
+            None
+        }
+        None => Some(warning),
+    }) {
+        Some(warning) => warning,
+        None => return,
+    };
+
+    let handler = WARN_HANDLER.lock().unwrap().clone();
+    match handler {
+        Some(handler) => {
+            if let Err(payload) = panic::catch_unwind(AssertUnwindSafe(|| handler(&warning))) {
+                PANIC.with(|p| *p.borrow_mut() = Some(payload));
+            }
+        }
+        None => log_warning(&warning),
+    }
+}
+
+#[cfg(feature = "log")]
+fn log_warning(warning: &Warning) {
+    if warning.verbose {
+        log::info!(target: "onig", "{}", warning);
+    } else {
+        log::warn!(target: "onig", "{}", warning);
+    }
+}
+
+#[cfg(not(feature = "log"))]
+fn log_warning(_warning: &Warning) {}
+
+impl Regex {
+    /// Compile a Regex and Collect its Warnings
+    ///
+    /// Compiles `pattern` as `Regex::with_options_and_encoding` does,
+    /// returning any warnings Oniguruma raised while compiling it
+    /// alongside the regex. These warnings aren't passed to the handler
+    /// set with `set_warn_handler`.
+    ///
+    /// # Arguments
+    ///
+    ///  * `pattern` - The regex pattern to compile
+    ///  * `option` - The regex compilation options
+    ///  * `syntax` - The syntax which the pattern is written in
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use onig::{Regex, RegexOptions, Syntax};
+    ///
+    /// let (regex, warnings) =
+    ///     Regex::compile_with_warnings("[a]]", RegexOptions::REGEX_OPTION_NONE, Syntax::ruby())
+    ///         .unwrap();
+    /// assert!(regex.is_match("a]"));
+    /// assert_eq!(warnings.len(), 1);
+    /// assert!(warnings[0].message().contains("']' without escape"));
+    /// ```
+    pub fn compile_with_warnings<T>(
+        pattern: T,
+        option: RegexOptions,
+        syntax: &Syntax,
+    ) -> Result<(Regex, Vec<Warning>), Error>
+    where
+        T: EncodedChars,
+    {
+        let previous = COLLECTED.with(|c| c.borrow_mut().replace(Vec::new()));
+        let result = Regex::with_options_and_encoding(pattern, option, syntax);
+        let warnings = COLLECTED.with(|c| std::mem::replace(&mut *c.borrow_mut(), previous));
+        result.map(|regex| (regex, warnings.unwrap_or_default()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_compile_with_warnings() {
+        let (regex, warnings) =
+            Regex::compile_with_warnings("[a]]", RegexOptions::REGEX_OPTION_NONE, Syntax::ruby())
+                .unwrap();
+        assert!(regex.is_match("a]"));
+        assert_eq!(
+            warnings,
+            vec![Warning {
+                message: "regular expression has ']' without escape: /[a]]/".to_string(),
+                verbose: false,
+            }]
+        );
+
+        let (_, warnings) =
+            Regex::compile_with_warnings("a+*", RegexOptions::REGEX_OPTION_NONE, Syntax::ruby())
+                .unwrap();
+        assert_eq!(warnings.len(), 1);
+        assert!(warnings[0].is_verbose());
+        assert!(warnings[0].message().starts_with("nested repeat operator"));
+
+        let (_, warnings) =
+            Regex::compile_with_warnings("[a]]", RegexOptions::REGEX_OPTION_NONE, Syntax::perl())
+                .unwrap();
+        assert!(warnings.is_empty());
+    }
+
+    #[test]
+    fn test_compile_with_warnings_error() {
+        let e =
+            Regex::compile_with_warnings("[a[](", RegexOptions::REGEX_OPTION_NONE, Syntax::ruby())
+                .unwrap_err();
+        assert_eq!(e.kind(), super::super::ErrorKind::Syntax);
+        assert!(COLLECTED.with(|c| c.borrow().is_none()));
+    }
+}
@@ -74,6 +74,10 @@ fn compile() {
         cc.define("ONIG_DEBUG_MATCH", Some("1"));
     }
 
+    if env_var_bool("CARGO_FEATURE_CASE_FOLD_TURKISH_AZERI").unwrap_or(false) {
+        cc.define("USE_UNICODE_CASE_FOLD_TURKISH_AZERI", None);
+    }
+
     if !src.exists() {
         panic!(
             "Unable to find source files in {}. Is oniguruma submodule checked out?\n\
@@ -240,6 +244,11 @@ pub fn main() {
                 for path in &lib.include_paths {
                     let header = path.join("oniguruma.h");
                     if header.exists() {
+                        if cfg!(feature = "case-fold-turkish-azeri") {
+                            println!(
+                                "cargo:warning=case-fold-turkish-azeri only applies to the bundled Oniguruma, not the system one"
+                            );
+                        }
                         bindgen_headers(&header.display().to_string());
                         return;
                     }